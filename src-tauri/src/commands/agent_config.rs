@@ -1,5 +1,9 @@
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AgentConfig {
@@ -9,291 +13,316 @@ pub struct AgentConfig {
     pub global_path: String,
     pub icon: String,
     pub category: String, // "Core" | "Community"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_format: Option<RuleFormat>,
+    #[serde(default)]
+    pub capabilities: AgentCapabilities,
 }
 
+// 代理能力描述，安装前用于兼容性检查
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentCapabilities {
+    #[serde(default = "true_val")]
+    pub supports_scripts: bool,
+    #[serde(default = "true_val")]
+    pub supports_nested_skills: bool,
+    #[serde(default)]
+    pub max_skill_size_bytes: Option<u64>,
+    #[serde(default)]
+    pub max_description_length: Option<usize>,
+    #[serde(default)]
+    pub frontmatter_fields: Option<Vec<String>>, // None = any field is accepted
+}
+
+impl Default for AgentCapabilities {
+    fn default() -> Self {
+        Self {
+            supports_scripts: true,
+            supports_nested_skills: true,
+            max_skill_size_bytes: None,
+            max_description_length: None,
+            frontmatter_fields: None,
+        }
+    }
+}
+
+fn true_val() -> bool { true }
+
+// 代理原生规则格式（不直接读取 SKILL.md 文件夹时使用）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RuleFormat {
+    pub kind: String, // "cursor_mdc" | "windsurf_rule" | "copilot_instructions"
+    pub project_path: String,
+    #[serde(default)]
+    pub global_path: Option<String>,
+}
+
+/// Agent definitions shipped with the app. Marketplaces can override or extend them
+/// with their own `.claude-plugin/agents.json` (same format).
+const BUNDLED_AGENTS: &str = include_str!("../../data/agents.json");
+const AGENTS_FILE_VERSION: u32 = 1;
+
+// agents.json 文件格式
+#[derive(Debug, Deserialize)]
+struct AgentsFile {
+    version: u32,
+    #[serde(default)]
+    agents: Vec<AgentDefinition>,
+}
+
+// 单条定义：覆盖已有代理时只需提供要修改的字段
+#[derive(Debug, Deserialize, Default)]
+struct AgentDefinition {
+    id: String,
+    name: Option<String>,
+    project_path: Option<String>,
+    global_path: Option<String>,
+    icon: Option<String>,
+    category: Option<String>,
+    rule_format: Option<RuleFormat>,
+    capabilities: Option<AgentCapabilities>,
+}
+
+static AGENTS: Lazy<RwLock<Vec<AgentConfig>>> = Lazy::new(|| RwLock::new(bundled_agents()));
+// Inputs of the last reload and the warnings it produced
+static LOADED: Lazy<Mutex<Option<LoadedSources>>> = Lazy::new(|| Mutex::new(None));
+
+// 定义来源：启用的仓库 (路径, agents.json 修改时间) 与自定义路径
+type SourcesKey = (Vec<(String, Option<SystemTime>)>, Vec<(String, String)>);
+type LoadedSources = (SourcesKey, Vec<String>);
+
+/// Agents ready to use. Agents whose global path does not resolve (an unset variable) are
+/// left out; `get_agent_path_diagnostics` still lists them with the reason.
 pub fn all_agents() -> Vec<AgentConfig> {
-    vec![
-        // --- Core Agents ---
-        AgentConfig {
-            id: "claude".into(),
-            name: "Claude Code".into(),
-            project_path: ".claude/skills/".into(),
-            global_path: "~/.claude/skills/".into(),
-            icon: "claude".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "cursor".into(),
-            name: "Cursor".into(),
-            project_path: ".cursor/skills/".into(),
-            global_path: "~/.cursor/skills/".into(),
-            icon: "cursor".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "windsurf".into(),
-            name: "Windsurf".into(),
-            project_path: ".windsurf/skills/".into(),
-            global_path: "~/.codeium/windsurf/skills/".into(),
-            icon: "windsurf".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "trae".into(),
-            name: "Trae".into(),
-            project_path: ".trae/skills/".into(),
-            global_path: "~/.trae/skills/".into(),
-            icon: "trae".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "github_copilot".into(),
-            name: "GitHub Copilot".into(),
-            project_path: ".agents/skills/".into(), // Changed to Vercel standard
-            global_path: "~/.copilot/skills/".into(),
-            icon: "github".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "antigravity".into(),
-            name: "Antigravity".into(),
-            project_path: ".agent/skills/".into(),
-            global_path: "~/.gemini/antigravity/skills/".into(),
-            icon: "antigravity".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "cline".into(),
-            name: "Cline".into(),
-            project_path: ".cline/skills/".into(),
-            global_path: "~/.cline/skills/".into(),
-            icon: "cline".into(),
-            category: "Core".into(),
-        },
-
-
-
-        AgentConfig {
-            id: "gemini".into(),
-            name: "Gemini CLI".into(),
-            project_path: ".agents/skills/".into(),
-            global_path: "~/.gemini/skills/".into(),
-            icon: "gemini".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "kiro".into(),
-            name: "Kiro CLI".into(),
-            project_path: ".kiro/skills/".into(),
-            global_path: "~/.kiro/skills/".into(),
-            icon: "kiro".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "kilocode".into(),
-            name: "Kilo Code".into(),
-            project_path: ".kilocode/skills/".into(),
-            global_path: "~/.kilocode/skills/".into(),
-            icon: "kilocode".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "openclaw".into(),
-            name: "OpenClaw".into(),
-            project_path: "skills/".into(),
-            global_path: "~/.openclaw/skills/".into(),
-            icon: "openclaw".into(),
-            category: "Core".into(),
-        },
-        AgentConfig {
-            id: "opencode".into(),
-            name: "OpenCode".into(),
-            project_path: ".agents/skills/".into(),
-            global_path: "~/.config/opencode/skills/".into(),
-            icon: "opencode".into(),
-            category: "Core".into(),
-        },
-
-
-        // --- Community Agents ---
-
-        AgentConfig {
-            id: "goose".into(),
-            name: "Goose".into(),
-            project_path: ".goose/skills/".into(),
-            global_path: "~/.config/goose/skills/".into(),
-            icon: "goose".into(),
-            category: "Community".into(),
-        },
-
-
-        AgentConfig {
-            id: "codebuddy".into(),
-            name: "CodeBuddy".into(),
-            project_path: ".codebuddy/skills/".into(),
-            global_path: "~/.codebuddy/skills/".into(),
-            icon: "codebuddy".into(),
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "continue".into(),
-            name: "Continue".into(),
-            project_path: ".continue/skills/".into(),
-            global_path: "~/.continue/skills/".into(),
-            icon: "continue".into(),
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "iflow".into(),
-            name: "iFlow CLI".into(),
-            project_path: ".iflow/skills/".into(),
-            global_path: "~/.iflow/skills/".into(),
-            icon: "iflow".into(), // Needs icon support
-            category: "Community".into(),
-        },
-
-        AgentConfig {
-            id: "roo".into(),
-            name: "Roo Code".into(),
-            project_path: ".roo/skills/".into(),
-            global_path: "~/.roo/skills/".into(),
-            icon: "roo".into(),
-            category: "Community".into(),
-        },
-
-
-
-        AgentConfig {
-            id: "amp".into(),
-            name: "Amp".into(),
-            project_path: ".agents/skills/".into(),
-            global_path: "~/.config/agents/skills/".into(),
-            icon: "amp".into(),
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "droid".into(),
-            name: "Droid".into(),
-            project_path: ".factory/skills/".into(),
-            global_path: "~/.factory/skills/".into(),
-            icon: "droid".into(),
-            category: "Community".into(),
-        },
-
-        AgentConfig {
-            id: "augment".into(),
-            name: "Augment".into(),
-            project_path: ".augment/skills/".into(),
-            global_path: "~/.augment/skills/".into(),
-            icon: "augment".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "command_code".into(),
-            name: "Command Code".into(),
-            project_path: ".commandcode/skills/".into(),
-            global_path: "~/.commandcode/skills/".into(),
-            icon: "command_code".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "junie".into(),
-            name: "Junie".into(),
-            project_path: ".junie/skills/".into(),
-            global_path: "~/.junie/skills/".into(),
-            icon: "junie".into(), // Needs icon support
-            category: "Community".into(),
-        },
-
-        AgentConfig {
-            id: "kode".into(),
-            name: "Kode".into(),
-            project_path: ".kode/skills/".into(),
-            global_path: "~/.kode/skills/".into(),
-            icon: "kode".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "mcpjam".into(),
-            name: "MCPJam".into(),
-            project_path: ".mcpjam/skills/".into(),
-            global_path: "~/.mcpjam/skills/".into(),
-            icon: "mcpjam".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "mistral_vibe".into(),
-            name: "Mistral Vibe".into(),
-            project_path: ".vibe/skills/".into(),
-            global_path: "~/.vibe/skills/".into(),
-            icon: "mistral".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "mux".into(),
-            name: "Mux".into(),
-            project_path: ".mux/skills/".into(),
-            global_path: "~/.mux/skills/".into(),
-            icon: "mux".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "pi".into(),
-            name: "Pi".into(),
-            project_path: ".pi/skills/".into(),
-            global_path: "~/.pi/agent/skills/".into(),
-            icon: "pi".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "qoder".into(),
-            name: "Qoder".into(),
-            project_path: ".qoder/skills/".into(),
-            global_path: "~/.qoder/skills/".into(),
-            icon: "qoder".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "qwen_code".into(),
-            name: "Qwen Code".into(),
-            project_path: ".qwen/skills/".into(),
-            global_path: "~/.qwen/skills/".into(),
-            icon: "qwen".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "zencoder".into(),
-            name: "Zencoder".into(),
-            project_path: ".zencoder/skills/".into(),
-            global_path: "~/.zencoder/skills/".into(),
-            icon: "zencoder".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "neovate".into(),
-            name: "Neovate".into(),
-            project_path: ".neovate/skills/".into(),
-            global_path: "~/.neovate/skills/".into(),
-            icon: "neovate".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "pochi".into(),
-            name: "Pochi".into(),
-            project_path: ".pochi/skills/".into(),
-            global_path: "~/.pochi/skills/".into(),
-            icon: "pochi".into(), // Needs icon support
-            category: "Community".into(),
-        },
-        AgentConfig {
-            id: "adal".into(),
-            name: "AdaL".into(),
-            project_path: ".adal/skills/".into(),
-            global_path: "~/.adal/skills/".into(),
-            icon: "adal".into(), // Needs icon support
-            category: "Community".into(),
-        },
-    ]
+    let mut agents = defined_agents();
+    agents.retain(|a| resolve_path(&a.global_path).is_ok());
+    agents
+}
+
+fn defined_agents() -> Vec<AgentConfig> {
+    AGENTS.read().map(|a| a.clone()).unwrap_or_else(|_| bundled_agents())
+}
+
+/// Agents from the bundled agents.json alone, without marketplace definitions or custom paths
+pub fn bundled_agents() -> Vec<AgentConfig> {
+    let mut agents = Vec::new();
+    let mut warnings = Vec::new();
+    match parse_agents_file(BUNDLED_AGENTS) {
+        Ok(defs) => merge_definitions(&mut agents, defs, "bundled", true, &mut warnings),
+        Err(e) => warnings.push(format!("bundled: {}", e)),
+    }
+    for w in warnings {
+        eprintln!("Agent definitions: {}", w);
+    }
+    agents
+}
+
+/// Rebuild the agent list from the bundled file plus every enabled marketplace.
+/// Repositories are applied from lowest to highest priority (lower number wins, as in
+/// `aggregate_marketplace_data`), so the highest-priority marketplace has the last word.
+/// Definition files are only re-read when a repository, its agents.json or a custom path changed.
+pub fn reload_agents(app: &tauri::AppHandle) -> Vec<String> {
+    let config = crate::commands::config_manager::load_config(app).ok();
+    let mut repos: Vec<_> = config
+        .as_ref()
+        .map(|c| c.marketplace.repositories.iter().filter(|r| r.enabled).cloned().collect())
+        .unwrap_or_default();
+    repos.sort_by_key(|r| std::cmp::Reverse(r.priority));
+
+    let agents_file = |local_path: &str| Path::new(local_path).join(".claude-plugin").join("agents.json");
+    let mut custom: Vec<(String, String)> = config
+        .as_ref()
+        .map(|c| c.agents.custom_paths.clone().into_iter().collect())
+        .unwrap_or_default();
+    custom.sort();
+    let key: SourcesKey = (
+        repos
+            .iter()
+            .map(|r| (r.local_path.clone(), std::fs::metadata(agents_file(&r.local_path)).and_then(|m| m.modified()).ok()))
+            .collect(),
+        custom,
+    );
+    if let Ok(loaded) = LOADED.lock() {
+        if let Some((_, warnings)) = loaded.as_ref().filter(|(k, _)| *k == key) {
+            return warnings.clone();
+        }
+    }
+
+    let mut agents = bundled_agents();
+    let mut warnings = Vec::new();
+
+    if let Some(config) = config {
+        let custom_paths = config.agents.custom_paths.clone();
+        for repo in repos {
+            let path = agents_file(&repo.local_path);
+            if !path.exists() {
+                continue;
+            }
+            match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| parse_agents_file(&content))
+            {
+                Ok(defs) => merge_definitions(&mut agents, defs, &repo.name, false, &mut warnings),
+                Err(e) => warnings.push(format!("{}: {}", repo.name, e)),
+            }
+        }
+
+        // User-configured global paths win over every definition source
+        for (agent_id, path) in custom_paths {
+            match agents.iter_mut().find(|a| a.id == agent_id) {
+                Some(agent) if !path.trim().is_empty() => agent.global_path = path,
+                Some(_) => {}
+                None => warnings.push(format!("custom path for unknown agent '{}' ignored", agent_id)),
+            }
+        }
+    }
+
+    for agent in &agents {
+        if let Err(e) = resolve_path(&agent.global_path) {
+            warnings.push(format!("agent '{}' skipped: {}", agent.id, e));
+        }
+    }
+
+    for w in &warnings {
+        eprintln!("Agent definitions: {}", w);
+    }
+    if let Ok(mut cache) = AGENTS.write() {
+        *cache = agents;
+    }
+    if let Ok(mut loaded) = LOADED.lock() {
+        *loaded = Some((key, warnings.clone()));
+    }
+    warnings
+}
+
+fn parse_agents_file(content: &str) -> Result<Vec<AgentDefinition>, String> {
+    let file: AgentsFile = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid agents.json: {}", e))?;
+    if file.version != AGENTS_FILE_VERSION {
+        return Err(format!(
+            "Unsupported agents.json version {} (expected {})",
+            file.version, AGENTS_FILE_VERSION
+        ));
+    }
+    Ok(file.agents)
+}
+
+/// Apply one source on top of `agents`. Known ids are patched field by field,
+/// unknown ids must be complete definitions. Invalid entries are skipped with a warning.
+/// Untrusted (marketplace) sources cannot move agents that already exist, and the global
+/// paths of the agents they add must stay inside the home directory.
+fn merge_definitions(
+    agents: &mut Vec<AgentConfig>,
+    mut defs: Vec<AgentDefinition>,
+    source: &str,
+    trusted: bool,
+    warnings: &mut Vec<String>,
+) {
+    let mut seen = HashSet::new();
+    if !trusted {
+        for def in defs.iter_mut() {
+            if !agents.iter().any(|a| a.id == def.id) {
+                continue;
+            }
+            if def.project_path.take().is_some() | def.global_path.take().is_some() | def.rule_format.take().is_some() {
+                warnings.push(format!("{}: paths of existing agent '{}' cannot be overridden", source, def.id));
+            }
+        }
+    }
+    for def in defs {
+        if let Err(e) = validate_definition(&def, trusted) {
+            warnings.push(format!("{}: skipped agent '{}': {}", source, def.id, e));
+            continue;
+        }
+        if !seen.insert(def.id.clone()) {
+            warnings.push(format!("{}: duplicate agent '{}' ignored", source, def.id));
+            continue;
+        }
+
+        if let Some(existing) = agents.iter_mut().find(|a| a.id == def.id) {
+            if let Some(v) = def.name { existing.name = v; }
+            if let Some(v) = def.project_path { existing.project_path = v; }
+            if let Some(v) = def.global_path { existing.global_path = v; }
+            if let Some(v) = def.icon { existing.icon = v; }
+            if let Some(v) = def.category { existing.category = v; }
+            if let Some(v) = def.rule_format { existing.rule_format = Some(v); }
+            if let Some(v) = def.capabilities { existing.capabilities = v; }
+            continue;
+        }
+
+        match (def.name, def.project_path, def.global_path) {
+            (Some(name), Some(project_path), Some(global_path)) => agents.push(AgentConfig {
+                icon: def.icon.unwrap_or_else(|| def.id.clone()),
+                category: def.category.unwrap_or_else(|| "Community".to_string()),
+                id: def.id,
+                name,
+                project_path,
+                global_path,
+                rule_format: def.rule_format,
+                capabilities: def.capabilities.unwrap_or_default(),
+            }),
+            _ => warnings.push(format!(
+                "{}: skipped agent '{}': new agents need name, project_path and global_path",
+                source, def.id
+            )),
+        }
+    }
+}
+
+fn validate_definition(def: &AgentDefinition, trusted: bool) -> Result<(), String> {
+    if def.id.is_empty()
+        || !def.id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+    {
+        return Err("id must be lowercase letters, digits, '_' or '-'".to_string());
+    }
+    if def.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        return Err("name must not be empty".to_string());
+    }
+    if let Some(p) = &def.project_path {
+        if p.trim().is_empty() || p.starts_with('/') || p.starts_with('~') || p.contains("..") {
+            return Err("project_path must be a relative path inside the project".to_string());
+        }
+    }
+    if let Some(p) = &def.global_path {
+        if p.trim().is_empty() {
+            return Err("global_path must not be empty".to_string());
+        }
+        if !trusted && !inside_home(p) {
+            return Err("global_path must resolve to a directory inside the home directory".to_string());
+        }
+    }
+    if let Some(f) = &def.rule_format {
+        if !crate::commands::format_adapters::is_supported_format(&f.kind) {
+            return Err(format!("unknown rule format '{}'", f.kind));
+        }
+        if f.project_path.trim().is_empty() || f.project_path.starts_with('/') || f.project_path.contains("..") {
+            return Err("rule_format.project_path must be a relative path inside the project".to_string());
+        }
+        if !trusted && f.global_path.as_deref().is_some_and(|p| !inside_home(p)) {
+            return Err("rule_format.global_path must resolve to a directory inside the home directory".to_string());
+        }
+    }
+    if let Some(c) = &def.category {
+        if c != "Core" && c != "Community" {
+            return Err(format!("unknown category '{}'", c));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a path template resolves strictly below the home directory
+fn inside_home(template: &str) -> bool {
+    inside_home_with(template, &|name| std::env::var(name).ok())
+}
+
+// Same home as `~` in templates, so a relocated HOME is honoured by both
+fn inside_home_with(template: &str, env: &dyn Fn(&str) -> Option<String>) -> bool {
+    let Ok(resolved) = resolve_path_with(template, env) else {
+        return false;
+    };
+    let home = PathBuf::from(home_dir_string(env));
+    let climbs = resolved.components().any(|c| matches!(c, std::path::Component::ParentDir));
+    !climbs && resolved.starts_with(&home) && resolved != home
 }
 
 #[tauri::command]
@@ -304,7 +333,7 @@ pub fn get_agents() -> Vec<AgentConfig> {
 pub fn get_agent_global_path(agent_id: &str) -> Option<PathBuf> {
     let agents = all_agents();
     let agent = agents.iter().find(|a| a.id == agent_id)?;
-    Some(resolve_home(&agent.global_path))
+    resolve_path(&agent.global_path).ok()
 }
 
 pub fn get_agent_project_path(agent_id: &str, project_root: &str) -> Option<PathBuf> {
@@ -315,16 +344,339 @@ pub fn get_agent_project_path(agent_id: &str, project_root: &str) -> Option<Path
     Some(p)
 }
 
-fn resolve_home(path_str: &str) -> PathBuf {
-    if path_str.starts_with("~") {
-        // Handle Windows and Unix home correctly
-        let home = std::env::var("USERPROFILE")
-            .or_else(|_| std::env::var("HOME"))
-            .unwrap_or_else(|_| ".".to_string());
-
-        let sub = path_str[1..].trim_start_matches(|c| c == '/' || c == '\\');
-        PathBuf::from(home).join(sub)
+/// Native rules directory for agents that declare a `rule_format`.
+/// Returns `None` for agents without one, or for global scope when the format has no global location.
+pub fn get_agent_rules_path(agent_id: &str, scope: &str, project_root: Option<&str>) -> Option<(RuleFormat, PathBuf)> {
+    let agents = all_agents();
+    let format = agents.iter().find(|a| a.id == agent_id)?.rule_format.clone()?;
+    let path = if scope == "global" {
+        resolve_path(format.global_path.as_deref()?).ok()?
     } else {
-        PathBuf::from(path_str)
+        PathBuf::from(project_root?).join(&format.project_path)
+    };
+    Some((format, path))
+}
+
+/// Expand a path template from an agent definition or the user's custom paths.
+///
+/// Supports a leading `~`, `$VAR`, `${VAR}` and `${VAR:-default}` (defaults may nest).
+/// `HOME` and the XDG base directories fall back to their usual locations when unset, so
+/// `$XDG_CONFIG_HOME/goose` means `~/.config/goose` unless the user relocated it.
+/// Any other unset variable is an error: `${FOO}/skills` must not become `/skills`.
+pub fn resolve_path(template: &str) -> Result<PathBuf, String> {
+    resolve_path_with(template, &|name| std::env::var(name).ok())
+}
+
+fn resolve_path_with(template: &str, env: &dyn Fn(&str) -> Option<String>) -> Result<PathBuf, String> {
+    let (expanded, vars) = expand_template(template, env);
+    match vars.iter().find(|v| v.value.is_none()) {
+        Some(var) => Err(format!("${{{}}} is not set in '{}'", var.name, template)),
+        None => Ok(PathBuf::from(expanded)),
+    }
+}
+
+// 路径模板中单个变量的解析情况
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PathVariable {
+    pub name: String,
+    pub value: Option<String>,
+    pub source: String, // "env" | "default" | "fallback" | "unset"
+}
+
+fn home_dir_string(env: &dyn Fn(&str) -> Option<String>) -> String {
+    // Handle Windows and Unix home correctly
+    env("USERPROFILE")
+        .filter(|h| !h.is_empty())
+        .or_else(|| env("HOME").filter(|h| !h.is_empty()))
+        .or_else(|| dirs::home_dir().map(|p| p.to_string_lossy().to_string()))
+        .unwrap_or_else(|| ".".to_string())
+}
+
+/// Built-in fallbacks for variables that have a well-known default location
+fn fallback_value(name: &str, env: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+    let home = home_dir_string(env);
+    let join = |sub: &str| PathBuf::from(&home).join(sub).to_string_lossy().to_string();
+    match name {
+        "HOME" => Some(home.clone()),
+        "XDG_CONFIG_HOME" => Some(join(".config")),
+        "XDG_DATA_HOME" => Some(join(".local/share")),
+        "XDG_STATE_HOME" => Some(join(".local/state")),
+        "XDG_CACHE_HOME" => Some(join(".cache")),
+        _ => None,
+    }
+}
+
+fn lookup_variable(name: &str, default: Option<&str>, env: &dyn Fn(&str) -> Option<String>) -> PathVariable {
+    let value = env(name).filter(|v| !v.is_empty());
+    // XDG values must be absolute, relative ones are ignored per the spec
+    let value = value.filter(|v| !name.starts_with("XDG_") || Path::new(v).is_absolute());
+
+    if let Some(v) = value {
+        return PathVariable { name: name.to_string(), value: Some(v), source: "env".to_string() };
+    }
+    if let Some(d) = default {
+        let (expanded, nested) = expand_template(d, env);
+        if nested.iter().all(|v| v.value.is_some()) {
+            return PathVariable { name: name.to_string(), value: Some(expanded), source: "default".to_string() };
+        }
+        return PathVariable { name: name.to_string(), value: None, source: "unset".to_string() };
+    }
+    match fallback_value(name, env) {
+        Some(v) => PathVariable { name: name.to_string(), value: Some(v), source: "fallback".to_string() },
+        None => PathVariable { name: name.to_string(), value: None, source: "unset".to_string() },
+    }
+}
+
+/// Index of the `}` closing a `${`, skipping over nested `${...}` in defaults
+fn closing_brace(inner: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the expanded string and every variable that was looked up on the way.
+fn expand_template(template: &str, env: &dyn Fn(&str) -> Option<String>) -> (String, Vec<PathVariable>) {
+    let mut out = String::new();
+    let mut vars = Vec::new();
+    let mut rest = template;
+
+    if let Some(after) = rest.strip_prefix('~') {
+        if after.is_empty() || after.starts_with(['/', '\\']) {
+            out.push_str(&home_dir_string(env));
+            rest = after;
+        }
+    }
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        let (name, default, consumed) = if let Some(inner) = after.strip_prefix('{') {
+            match closing_brace(inner) {
+                Some(close) => {
+                    let body = &inner[..close];
+                    match body.split_once(":-") {
+                        Some((n, d)) => (n, Some(d), close + 2),
+                        None => (body, None, close + 2),
+                    }
+                }
+                None => ("", None, 0),
+            }
+        } else {
+            let len = after
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .count();
+            (&after[..len], None, len)
+        };
+
+        let valid = !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            // Not a variable reference, keep the `$` literally
+            out.push('$');
+            rest = after;
+            continue;
+        }
+
+        let var = lookup_variable(name, default, env);
+        if let Some(ref v) = var.value {
+            out.push_str(v);
+        }
+        if !vars.contains(&var) {
+            vars.push(var);
+        }
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    (out, vars)
+}
+
+// 代理路径诊断信息
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentPathDiagnostics {
+    pub agent_id: String,
+    pub global_template: String,
+    pub global_resolved: String,
+    pub global_exists: bool,
+    pub custom_override: bool,
+    pub project_template: String,
+    pub project_resolved: Option<String>,
+    pub variables: Vec<PathVariable>,
+    pub error: Option<String>, // set when the agent is skipped because its path does not resolve
+}
+
+/// 显示每个代理路径模板的解析结果
+#[tauri::command]
+pub fn get_agent_path_diagnostics(app: tauri::AppHandle, project_path: Option<String>) -> Vec<AgentPathDiagnostics> {
+    reload_agents(&app);
+    let custom_paths = crate::commands::config_manager::load_config(&app)
+        .map(|c| c.agents.custom_paths)
+        .unwrap_or_default();
+    let env = |name: &str| std::env::var(name).ok();
+
+    defined_agents()
+        .into_iter()
+        .map(|agent| {
+            let (resolved, variables) = expand_template(&agent.global_path, &env);
+            let resolved = PathBuf::from(resolved);
+            let error = resolve_path(&agent.global_path).err();
+            AgentPathDiagnostics {
+                global_exists: error.is_none() && resolved.is_dir(),
+                global_resolved: resolved.to_string_lossy().to_string(),
+                custom_override: custom_paths.contains_key(&agent.id),
+                project_resolved: project_path
+                    .as_ref()
+                    .and_then(|p| get_agent_project_path(&agent.id, p))
+                    .map(|p| p.to_string_lossy().to_string()),
+                global_template: agent.global_path,
+                project_template: agent.project_path,
+                agent_id: agent.id,
+                variables,
+                error,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_agents_are_valid() {
+        let agents = bundled_agents();
+        assert!(agents.iter().any(|a| a.id == "claude"));
+        let ids: HashSet<_> = agents.iter().map(|a| a.id.clone()).collect();
+        assert_eq!(ids.len(), agents.len());
+    }
+
+    #[test]
+    fn test_merge_overrides_and_extends() {
+        let mut agents = bundled_agents();
+        let defs = parse_agents_file(
+            r#"{
+                "version": 1,
+                "agents": [
+                    { "id": "cursor", "global_path": "~/.cursor/custom-skills/" },
+                    { "id": "newagent", "name": "New Agent", "project_path": ".new/skills/", "global_path": "~/.new/skills/" },
+                    { "id": "incomplete", "name": "Incomplete" },
+                    { "id": "Bad Id", "name": "Bad" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut warnings = Vec::new();
+        merge_definitions(&mut agents, defs, "test", true, &mut warnings);
+
+        let cursor = agents.iter().find(|a| a.id == "cursor").unwrap();
+        assert_eq!(cursor.global_path, "~/.cursor/custom-skills/");
+        assert_eq!(cursor.name, "Cursor");
+
+        let new_agent = agents.iter().find(|a| a.id == "newagent").unwrap();
+        assert_eq!(new_agent.icon, "newagent");
+        assert_eq!(new_agent.category, "Community");
+
+        assert!(!agents.iter().any(|a| a.id == "incomplete"));
+        assert_eq!(warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_marketplace_cannot_move_agents() {
+        let mut agents = bundled_agents();
+        let defs = parse_agents_file(
+            r#"{
+                "version": 1,
+                "agents": [
+                    { "id": "claude", "name": "Claude", "global_path": "~/", "project_path": "x/" },
+                    { "id": "rooted", "name": "Rooted", "project_path": ".r/skills/", "global_path": "/etc/skills/" },
+                    { "id": "homed", "name": "Homed", "project_path": ".h/skills/", "global_path": "~" },
+                    { "id": "fine", "name": "Fine", "project_path": ".f/skills/", "global_path": "~/.fine/skills/" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut warnings = Vec::new();
+        merge_definitions(&mut agents, defs, "market", false, &mut warnings);
+
+        let claude = agents.iter().find(|a| a.id == "claude").unwrap();
+        assert_eq!((claude.name.as_str(), claude.global_path.as_str()), ("Claude", "~/.claude/skills/"));
+        assert_eq!(claude.project_path, ".claude/skills/");
+        assert!(!agents.iter().any(|a| a.id == "rooted" || a.id == "homed"));
+        assert!(agents.iter().any(|a| a.id == "fine"));
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[test]
+    fn test_expand_template() {
+        let env = |name: &str| match name {
+            "HOME" => Some("/home/me".to_string()),
+            "XDG_CONFIG_HOME" => Some("/xdg".to_string()),
+            "TOOLS" => Some("/opt/tools".to_string()),
+            _ => None,
+        };
+        assert_eq!(expand_template("~/.claude/skills/", &env).0, "/home/me/.claude/skills/");
+        assert_eq!(expand_template("$XDG_CONFIG_HOME/goose/skills/", &env).0, "/xdg/goose/skills/");
+        assert_eq!(expand_template("${TOOLS}/skills", &env).0, "/opt/tools/skills");
+        assert_eq!(expand_template("${MISSING:-$HOME/fallback}/skills", &env).0, "/home/me/fallback/skills");
+        assert_eq!(expand_template("/price$/x", &env).0, "/price$/x");
+    }
+
+    #[test]
+    fn test_inside_home_uses_the_template_home() {
+        let env = |name: &str| match name {
+            "HOME" => Some("/home/me".to_string()),
+            "USERPROFILE" => Some(String::new()),
+            _ => None,
+        };
+        assert!(inside_home_with("~/.agent/skills/", &env));
+        assert!(inside_home_with("$HOME/.agent/skills/", &env));
+        assert!(!inside_home_with("~", &env));
+        assert!(!inside_home_with("~/../other/skills", &env));
+        assert!(!inside_home_with("/etc/agent/skills", &env));
+    }
+
+    #[test]
+    fn test_unset_variables_and_nested_defaults() {
+        let env = |name: &str| match name {
+            "HOME" => Some("/home/me".to_string()),
+            "B" => Some("/b".to_string()),
+            _ => None,
+        };
+        assert_eq!(expand_template("${A:-${B}/x}/skills", &env).0, "/b/x/skills");
+        assert_eq!(expand_template("${A:-${B:-/c}}/skills", &env).0, "/b/skills");
+
+        let (_, vars) = expand_template("${FOO}/skills", &env);
+        assert_eq!((vars[0].name.as_str(), vars[0].source.as_str()), ("FOO", "unset"));
+        let (_, vars) = expand_template("${A:-${MISSING}}/skills", &env);
+        assert!(vars[0].value.is_none());
+
+        assert!(resolve_path("${SKILLBOX_TEST_SURELY_UNSET}/skills").is_err());
+        assert!(resolve_path("~/.claude/skills/").is_ok());
+    }
+
+    #[test]
+    fn test_xdg_fallback_when_unset_or_relative() {
+        let env = |name: &str| match name {
+            "HOME" => Some("/home/me".to_string()),
+            "XDG_CONFIG_HOME" => Some("relative/dir".to_string()),
+            _ => None,
+        };
+        let (path, vars) = expand_template("$XDG_CONFIG_HOME/opencode/skills/", &env);
+        assert_eq!(PathBuf::from(path), PathBuf::from("/home/me/.config/opencode/skills/"));
+        assert_eq!(vars[0].source, "fallback");
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        assert!(parse_agents_file(r#"{ "version": 99, "agents": [] }"#).is_err());
     }
 }
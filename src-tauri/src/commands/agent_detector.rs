@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::commands::agent_config::{self, AgentConfig};
+
+// 单个代理的检测结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentDetection {
+    pub agent_id: String,
+    pub status: String, // "installed" | "likely_installed" | "absent"
+    pub global_config_dir: Option<String>,
+    pub global_skills_exists: bool,
+    pub project_marker: Option<String>,
+    pub project_marker_exists: bool,
+    pub project_skills_exists: bool,
+    pub evidence: Vec<String>,
+}
+
+/// 检测本机（以及可选项目）中实际存在的代理
+#[tauri::command]
+pub fn detect_agents(project_path: Option<String>) -> Result<Vec<AgentDetection>, String> {
+    let agents = agent_config::all_agents();
+    let project_root = project_path
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from);
+
+    if let Some(ref root) = project_root {
+        if !root.is_dir() {
+            return Err(format!("Project path does not exist: {}", root.display()));
+        }
+    }
+
+    Ok(agents
+        .iter()
+        .map(|agent| detect_agent(agent, &agents, project_root.as_deref()))
+        .collect())
+}
+
+fn detect_agent(agent: &AgentConfig, all: &[AgentConfig], project_root: Option<&Path>) -> AgentDetection {
+    let mut evidence = Vec::new();
+
+    // Global: the skills dir itself is proof, its parent (the agent's config dir) is a strong hint
+    let global_skills = agent_config::resolve_path(&agent.global_path).ok();
+    let global_skills_exists = global_skills.as_ref().is_some_and(|p| p.is_dir());
+    let global_config_dir = global_skills.as_deref().and_then(config_dir_for);
+    let global_config_exists = global_config_dir.as_ref().is_some_and(|p| p.is_dir());
+
+    if let (true, Some(p)) = (global_skills_exists, &global_skills) {
+        evidence.push(format!("Global skills directory found: {}", p.display()));
+    } else if let (true, Some(p)) = (global_config_exists, &global_config_dir) {
+        evidence.push(format!("Global config directory found: {}", p.display()));
+    }
+
+    // Project: marker directories such as `.cursor` or `.windsurf`
+    let project_marker = project_marker_for(agent);
+    let mut project_marker_exists = false;
+    let mut project_skills_exists = false;
+
+    if let Some(root) = project_root {
+        let skills_dir = root.join(&agent.project_path);
+        if skills_dir.is_dir() {
+            project_skills_exists = true;
+            evidence.push(format!("Project skills directory found: {}", skills_dir.display()));
+        }
+
+        if let Some(ref marker) = project_marker {
+            if root.join(marker).is_dir() {
+                project_marker_exists = true;
+                // Markers shared by several agents (e.g. `.agents`) only prove that one of them is used
+                if is_shared_marker(marker, all) {
+                    evidence.push(format!("Shared project marker found: {}", marker));
+                } else {
+                    evidence.push(format!("Project marker found: {}", marker));
+                }
+            }
+        }
+    }
+
+    let unique_marker = project_marker_exists
+        && project_marker.as_deref().is_some_and(|m| !is_shared_marker(m, all));
+
+    let status = if global_skills_exists || project_skills_exists {
+        "installed"
+    } else if global_config_exists || unique_marker {
+        "likely_installed"
+    } else {
+        "absent"
+    };
+
+    AgentDetection {
+        agent_id: agent.id.clone(),
+        status: status.to_string(),
+        global_config_dir: global_config_dir.map(|p| p.to_string_lossy().to_string()),
+        global_skills_exists,
+        project_marker,
+        project_marker_exists,
+        project_skills_exists,
+        evidence,
+    }
+}
+
+/// The agent's own config directory, i.e. the parent of its global skills directory
+/// (`~/.codeium/windsurf/skills/` -> `~/.codeium/windsurf`). The home directory itself never counts.
+fn config_dir_for(skills_dir: &Path) -> Option<PathBuf> {
+    let parent = skills_dir.parent()?;
+    if let Some(home) = dirs::home_dir() {
+        if parent == home || home.starts_with(parent) {
+            return None;
+        }
+    }
+    Some(parent.to_path_buf())
+}
+
+/// First component of the project skills path (`.cursor/skills/` -> `.cursor`).
+/// Paths without a dedicated directory (e.g. `skills/`) have no marker.
+pub fn project_marker_for(agent: &AgentConfig) -> Option<String> {
+    let rel = agent.project_path.trim_start_matches("./").replace('\\', "/");
+    let mut parts = rel.split('/').filter(|s| !s.is_empty());
+    let first = parts.next()?;
+    parts.next()?;
+    Some(first.to_string())
+}
+
+fn is_shared_marker(marker: &str, all: &[AgentConfig]) -> bool {
+    all.iter()
+        .filter(|a| project_marker_for(a).as_deref() == Some(marker))
+        .count()
+        > 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::agent_config::AgentCapabilities;
+    use std::fs;
+
+    fn agent(id: &str, project_path: &str, global_path: &Path) -> AgentConfig {
+        AgentConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            project_path: project_path.to_string(),
+            global_path: global_path.to_string_lossy().to_string(),
+            icon: id.to_string(),
+            category: "Community".to_string(),
+            rule_format: None,
+            capabilities: AgentCapabilities::default(),
+        }
+    }
+
+    fn status(agent: &AgentConfig, all: &[AgentConfig], project: Option<&Path>) -> String {
+        detect_agent(agent, all, project).status
+    }
+
+    #[test]
+    fn test_global_skills_prove_and_config_dir_hints() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = tmp.path().join("home/.tool");
+        let a = agent("tool", ".tool/skills/", &config.join("skills"));
+        let all = [a.clone()];
+        assert_eq!(status(&a, &all, None), "absent");
+
+        fs::create_dir_all(&config).unwrap();
+        assert_eq!(status(&a, &all, None), "likely_installed");
+
+        fs::create_dir_all(config.join("skills")).unwrap();
+        assert_eq!(status(&a, &all, None), "installed");
+    }
+
+    #[test]
+    fn test_project_markers_and_shared_dirs() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let project = tmp.path().join("project");
+        let nowhere = tmp.path().join("none/skills");
+        let cursor = agent("cursor", ".cursor/skills/", &nowhere);
+        let amp = agent("amp", ".agents/skills/", &nowhere);
+        let kode = agent("kode", ".agents/skills/", &nowhere);
+        let all = [cursor.clone(), amp.clone(), kode.clone()];
+
+        // A marker of its own hints at the agent, a shared one (`.agents`) proves nothing
+        fs::create_dir_all(project.join(".cursor")).unwrap();
+        fs::create_dir_all(project.join(".agents")).unwrap();
+        assert_eq!(status(&cursor, &all, Some(&project)), "likely_installed");
+        assert_eq!(status(&amp, &all, Some(&project)), "absent");
+        assert!(detect_agent(&amp, &all, Some(&project)).evidence[0].starts_with("Shared project marker"));
+
+        // A skills directory is what the agents read, shared or not
+        fs::create_dir_all(project.join(".cursor/skills")).unwrap();
+        fs::create_dir_all(project.join(".agents/skills")).unwrap();
+        assert_eq!(status(&cursor, &all, Some(&project)), "installed");
+        assert_eq!(status(&amp, &all, Some(&project)), "installed");
+        assert_eq!(status(&kode, &all, Some(&project)), "installed");
+    }
+}
@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::agent_config::{self, AgentConfig};
+use crate::commands::{frontmatter, marketplace, plugin_installer, plugin_scanner};
+
+// 兼容性问题
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompatibilityIssue {
+    pub agent_id: String,
+    pub skill_name: String,
+    pub code: String, // "scripts_unsupported" | "nested_skills_unsupported" | "size_limit" | ...
+    pub severity: String, // "warning" | "error"
+    pub message: String,
+}
+
+// 安装计划中的单个条目
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstallPlanItem {
+    pub agent_id: String,
+    pub skill_name: String,
+    pub source_path: String,
+    pub target_path: Option<String>,
+    pub action: String, // "copy" | "convert" | "skip"
+}
+
+// 安装计划
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstallPlan {
+    pub items: Vec<InstallPlanItem>,
+    pub warnings: Vec<CompatibilityIssue>,
+    pub blocked: bool, // true when at least one item will be refused
+}
+
+/// What the installer needs to know about a skill to check it against an agent
+struct SkillFacts {
+    name: String,
+    has_scripts: bool,
+    has_nested_skills: bool,
+    size_bytes: u64,
+    description_length: usize,
+    frontmatter_keys: Vec<String>,
+}
+
+fn collect_facts(skill_dir: &Path) -> SkillFacts {
+    let name = skill_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let parsed = frontmatter::parse_file(&skill_dir.join("SKILL.md")).ok();
+
+    SkillFacts {
+        name,
+        has_scripts: skill_dir.join("scripts").is_dir(),
+        has_nested_skills: contains_nested_skill(skill_dir, 4),
+        size_bytes: plugin_scanner::calculate_dir_size(skill_dir).unwrap_or(0),
+        description_length: parsed
+            .as_ref()
+            .and_then(|p| p.description())
+            .map_or(0, |d| d.trim().chars().count()),
+        frontmatter_keys: parsed.map(|p| p.keys).unwrap_or_default(),
+    }
+}
+
+fn contains_nested_skill(dir: &Path, depth: u32) -> bool {
+    if depth == 0 {
+        return false;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        path.is_dir() && (path.join("SKILL.md").exists() || contains_nested_skill(&path, depth - 1))
+    })
+}
+
+/// Check one skill against one agent. `native` is set when the skill will be converted
+/// into the agent's rule format, where only the SKILL.md body survives.
+pub fn check_skill(agent: &AgentConfig, skill_dir: &Path, native: bool) -> Vec<CompatibilityIssue> {
+    let facts = collect_facts(skill_dir);
+    let caps = &agent.capabilities;
+    let mut issues = Vec::new();
+    let mut push = |code: &str, severity: &str, message: String| {
+        issues.push(CompatibilityIssue {
+            agent_id: agent.id.clone(),
+            skill_name: facts.name.clone(),
+            code: code.to_string(),
+            severity: severity.to_string(),
+            message,
+        })
+    };
+
+    if facts.has_scripts && (native || !caps.supports_scripts) {
+        push(
+            "scripts_unsupported",
+            "warning",
+            format!("{} cannot run the scripts/ shipped with this skill", agent.name),
+        );
+    }
+
+    // The outer skill still works, only the nested ones are not picked up on their own
+    if facts.has_nested_skills && (native || !caps.supports_nested_skills) {
+        push(
+            "nested_skills_unsupported",
+            "warning",
+            format!("{} does not load skills nested inside other skill folders", agent.name),
+        );
+    }
+
+    if let Some(limit) = caps.max_skill_size_bytes {
+        if facts.size_bytes > limit {
+            push(
+                "size_limit",
+                "error",
+                format!("Skill is {} bytes, {} accepts at most {} bytes", facts.size_bytes, agent.name, limit),
+            );
+        }
+    }
+
+    if let Some(limit) = caps.max_description_length {
+        if facts.description_length > limit {
+            push(
+                "description_too_long",
+                "warning",
+                format!("Description has {} characters, {} truncates after {}", facts.description_length, agent.name, limit),
+            );
+        }
+    }
+
+    if let Some(ref allowed) = caps.frontmatter_fields {
+        for key in facts.frontmatter_keys.iter().filter(|k| !allowed.contains(k)) {
+            push(
+                "unsupported_frontmatter",
+                "warning",
+                format!("{} ignores the frontmatter field '{}'", agent.name, key),
+            );
+        }
+    }
+
+    issues
+}
+
+pub fn has_blocking_issue(issues: &[CompatibilityIssue]) -> bool {
+    issues.iter().any(|i| i.severity == "error")
+}
+
+fn build_plan(
+    skill_dirs: &[PathBuf],
+    agents: &[String],
+    scope: &str,
+    scope_path: Option<&str>,
+    native_format: bool,
+) -> InstallPlan {
+    let all = agent_config::all_agents();
+    let mut plan = InstallPlan { items: Vec::new(), warnings: Vec::new(), blocked: false };
+
+    for agent_id in agents {
+        let Some(agent) = all.iter().find(|a| &a.id == agent_id) else {
+            continue;
+        };
+        let native = if native_format {
+            agent_config::get_agent_rules_path(agent_id, scope, scope_path)
+        } else {
+            None
+        };
+        let target_root = if scope == "global" {
+            agent_config::get_agent_global_path(agent_id)
+        } else {
+            scope_path.and_then(|p| agent_config::get_agent_project_path(agent_id, p))
+        };
+
+        for skill_dir in skill_dirs {
+            let issues = check_skill(agent, skill_dir, native.is_some());
+            let blocked = has_blocking_issue(&issues);
+            let skill_name = skill_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+            let (action, target) = match (&native, &target_root) {
+                _ if blocked => ("skip", None),
+                (Some((_, rules_dir)), _) => ("convert", Some(rules_dir.clone())),
+                (None, Some(root)) => ("copy", Some(root.join(&skill_name))),
+                (None, None) => ("skip", None),
+            };
+
+            plan.blocked |= blocked;
+            plan.warnings.extend(issues);
+            plan.items.push(InstallPlanItem {
+                agent_id: agent_id.clone(),
+                skill_name,
+                source_path: skill_dir.to_string_lossy().to_string(),
+                target_path: target.map(|t| t.to_string_lossy().to_string()),
+                action: action.to_string(),
+            });
+        }
+    }
+
+    plan
+}
+
+/// 预览市场插件的安装计划（包含兼容性警告）
+#[tauri::command]
+pub fn plan_plugin_install(
+    app: tauri::AppHandle,
+    plugin_name: String,
+    agents: Vec<String>,
+    scope_type: String,
+    scope_path: Option<String>,
+    native_format: Option<bool>,
+) -> Result<InstallPlan, String> {
+    let data = marketplace::get_marketplace_data(app.clone())?;
+    let plugin = data
+        .plugins
+        .iter()
+        .find(|p| p.name == plugin_name)
+        .ok_or_else(|| format!("Plugin {} not found", plugin_name))?;
+    let source_root = plugin_installer::resolve_source_root(&app, plugin)?;
+
+    let skill_dirs: Vec<PathBuf> = plugin
+        .skills
+        .iter()
+        .map(|s| source_root.join(s.trim_start_matches("./")))
+        .collect();
+
+    Ok(build_plan(
+        &skill_dirs,
+        &agents,
+        &scope_type,
+        scope_path.as_deref(),
+        native_format.unwrap_or(false),
+    ))
+}
+
+/// 预览本地 skill 的安装计划（包含兼容性警告）
+#[tauri::command]
+pub fn plan_local_skill_install(
+    skill_path: String,
+    scope: String,
+    selected_agents: Vec<String>,
+    scope_path: Option<String>,
+    native_format: Option<bool>,
+) -> Result<InstallPlan, String> {
+    let source = PathBuf::from(&skill_path);
+    if !source.join("SKILL.md").exists() {
+        return Err(format!("Invalid skill directory (missing SKILL.md): {}", skill_path));
+    }
+
+    Ok(build_plan(
+        &[source],
+        &selected_agents,
+        &scope,
+        scope_path.as_deref(),
+        native_format.unwrap_or(false),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::agent_config::AgentCapabilities;
+
+    fn agent(capabilities: AgentCapabilities) -> AgentConfig {
+        AgentConfig {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            project_path: ".test/skills/".to_string(),
+            global_path: "~/.test/skills/".to_string(),
+            icon: "test".to_string(),
+            category: "Community".to_string(),
+            rule_format: None,
+            capabilities,
+        }
+    }
+
+    fn skill(description: &str) -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().unwrap();
+        let content = format!("---\nname: demo\ndescription: {}\nlicense: MIT\n---\nBody\n", description);
+        fs::write(dir.path().join("SKILL.md"), content).unwrap();
+        dir
+    }
+
+    fn codes(agent: &AgentConfig, dir: &Path, native: bool) -> Vec<String> {
+        check_skill(agent, dir, native).into_iter().map(|i| i.code).collect()
+    }
+
+    #[test]
+    fn test_plain_skill_is_compatible() {
+        let dir = skill("Short");
+        assert!(codes(&agent(AgentCapabilities::default()), dir.path(), false).is_empty());
+    }
+
+    #[test]
+    fn test_scripts_unsupported() {
+        let dir = skill("Short");
+        fs::create_dir(dir.path().join("scripts")).unwrap();
+        let no_scripts = agent(AgentCapabilities { supports_scripts: false, ..Default::default() });
+        assert_eq!(codes(&no_scripts, dir.path(), false), vec!["scripts_unsupported"]);
+        // Native rule files drop scripts/ whatever the agent supports
+        assert_eq!(codes(&agent(AgentCapabilities::default()), dir.path(), true), vec!["scripts_unsupported"]);
+    }
+
+    #[test]
+    fn test_nested_skills_unsupported_warns() {
+        let dir = skill("Short");
+        fs::create_dir_all(dir.path().join("inner")).unwrap();
+        fs::write(dir.path().join("inner/SKILL.md"), "---\nname: inner\n---\n").unwrap();
+        let flat = agent(AgentCapabilities { supports_nested_skills: false, ..Default::default() });
+        let issues = check_skill(&flat, dir.path(), false);
+        assert_eq!(issues.iter().map(|i| i.code.as_str()).collect::<Vec<_>>(), vec!["nested_skills_unsupported"]);
+        assert!(!has_blocking_issue(&issues));
+        assert!(codes(&agent(AgentCapabilities::default()), dir.path(), false).is_empty());
+    }
+
+    #[test]
+    fn test_size_limit_blocks() {
+        let dir = skill("Short");
+        let small = agent(AgentCapabilities { max_skill_size_bytes: Some(10), ..Default::default() });
+        let issues = check_skill(&small, dir.path(), false);
+        assert_eq!(issues[0].code, "size_limit");
+        assert!(has_blocking_issue(&issues));
+    }
+
+    #[test]
+    fn test_description_too_long_warns() {
+        let dir = skill(&"x".repeat(30));
+        let strict = agent(AgentCapabilities { max_description_length: Some(20), ..Default::default() });
+        let issues = check_skill(&strict, dir.path(), false);
+        assert_eq!(issues[0].code, "description_too_long");
+        assert!(!has_blocking_issue(&issues));
+    }
+
+    #[test]
+    fn test_unsupported_frontmatter_warns() {
+        let dir = skill("Short");
+        let fields = vec!["name".to_string(), "description".to_string()];
+        let strict = agent(AgentCapabilities { frontmatter_fields: Some(fields), ..Default::default() });
+        let issues = check_skill(&strict, dir.path(), false);
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].code.as_str(), issues[0].severity.as_str()), ("unsupported_frontmatter", "warning"));
+        assert!(issues[0].message.contains("'license'"));
+    }
+
+    #[test]
+    fn test_bundled_agents_declare_capabilities() {
+        let agents = agent_config::bundled_agents();
+        assert!(!agents.is_empty());
+        for a in &agents {
+            // MCPJam is the only agent declared without script support
+            assert_eq!(a.capabilities.supports_scripts, a.id != "mcpjam", "{}", a.id);
+            assert!(!a.capabilities.supports_nested_skills, "{}", a.id);
+            assert_eq!(a.capabilities.max_description_length, Some(1024), "{}", a.id);
+        }
+
+        // A nested skill never stops an install for any bundled agent
+        let dir = skill("Short");
+        fs::create_dir_all(dir.path().join("inner")).unwrap();
+        fs::write(dir.path().join("inner/SKILL.md"), "---\nname: inner\n---\n").unwrap();
+        assert!(agents.iter().all(|a| !has_blocking_issue(&check_skill(a, dir.path(), false))));
+    }
+}
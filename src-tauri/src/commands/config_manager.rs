@@ -1,9 +1,10 @@
 use std::fs;
 use std::path::PathBuf;
-use crate::types::{AppConfig, GeneralSettings, RepositoryInfo};
+use crate::types::{AppConfig, GeneralSettings, RefreshSettings, RepositoryInfo, WorkspaceSettings};
+use crate::commands::{agent_config, credential_store};
 use tauri::Manager;
 
-fn get_config_path() -> Result<PathBuf, String> {
+pub fn get_config_path() -> Result<PathBuf, String> {
     let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
     let config_dir = home_dir.join(".skillbox-studio");
     if !config_dir.exists() {
@@ -69,6 +70,7 @@ pub fn load_config(app: &tauri::AppHandle) -> Result<AppConfig, String> {
              local_path: official_local_path,
              last_updated: chrono::Utc::now().to_rfc3339(),
              auth_type: "public".to_string(),
+             pinned: false,
         }
     };
     
@@ -93,10 +95,16 @@ pub fn get_app_config(app: tauri::AppHandle) -> Result<AppConfig, String> {
 }
 
 #[tauri::command]
-pub fn save_app_config(config: AppConfig) -> Result<(), String> {
+pub fn save_app_config(app: tauri::AppHandle, config: AppConfig) -> Result<(), String> {
+    save_config(&app, config)
+}
+
+/// Write the config, then pick up agent definitions of added, removed or updated repositories
+pub fn save_config(app: &tauri::AppHandle, config: AppConfig) -> Result<(), String> {
     let path = get_config_path()?;
     let content = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
     fs::write(path, content).map_err(|e| e.to_string())?;
+    agent_config::reload_agents(app);
     Ok(())
 }
 
@@ -104,25 +112,44 @@ pub fn save_app_config(config: AppConfig) -> Result<(), String> {
 pub fn update_general_settings(app: tauri::AppHandle, settings: GeneralSettings) -> Result<(), String> {
     let mut config = load_config(&app)?;
     config.general = settings;
-    save_app_config(config)
+    save_config(&app, config)
+}
+
+#[tauri::command]
+pub fn update_workspace_settings(app: tauri::AppHandle, settings: WorkspaceSettings) -> Result<(), String> {
+    let mut config = load_config(&app)?;
+    config.workspace = settings;
+    save_config(&app, config)
+}
+
+#[tauri::command]
+pub fn update_refresh_settings(app: tauri::AppHandle, settings: RefreshSettings) -> Result<(), String> {
+    let mut config = load_config(&app)?;
+    config.refresh = settings;
+    save_config(&app, config)
 }
 
 #[tauri::command]
 pub fn add_repository_to_config(app: tauri::AppHandle, repo: RepositoryInfo) -> Result<(), String> {
+    credential_store::check_url(&repo.url)?;
     let mut config = load_config(&app)?;
     // Check if exists
     if config.marketplace.repositories.iter().any(|r| r.id == repo.id) {
         return Err("Repository with this ID already exists".to_string());
     }
     config.marketplace.repositories.push(repo);
-    save_app_config(config)
+    save_config(&app, config)
 }
 
 #[tauri::command]
 pub fn remove_repository_from_config(app: tauri::AppHandle, repo_id: String) -> Result<(), String> {
     let mut config = load_config(&app)?;
     config.marketplace.repositories.retain(|r| r.id != repo_id);
-    save_app_config(config)
+    save_config(&app, config)?;
+    if let Err(e) = credential_store::remove(&repo_id) {
+        eprintln!("Failed to remove credentials of repository {}: {}", repo_id, e);
+    }
+    Ok(())
 }
 
 #[tauri::command]
@@ -130,7 +157,18 @@ pub fn update_repository_enabled(app: tauri::AppHandle, repo_id: String, enabled
     let mut config = load_config(&app)?;
     if let Some(repo) = config.marketplace.repositories.iter_mut().find(|r| r.id == repo_id) {
         repo.enabled = enabled;
-        save_app_config(config)
+        save_config(&app, config)
+    } else {
+        Err("Repository not found".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_repository_pinned(app: tauri::AppHandle, repo_id: String, pinned: bool) -> Result<(), String> {
+    let mut config = load_config(&app)?;
+    if let Some(repo) = config.marketplace.repositories.iter_mut().find(|r| r.id == repo_id) {
+        repo.pinned = pinned;
+        save_config(&app, config)
     } else {
         Err("Repository not found".to_string())
     }
@@ -138,11 +176,12 @@ pub fn update_repository_enabled(app: tauri::AppHandle, repo_id: String, enabled
 
 #[tauri::command]
 pub fn update_repository_in_config(app: tauri::AppHandle, repo_id: String, name: String, url: String) -> Result<(), String> {
+    credential_store::check_url(&url)?;
     let mut config = load_config(&app)?;
     if let Some(repo) = config.marketplace.repositories.iter_mut().find(|r| r.id == repo_id) {
         repo.name = name;
         repo.url = url;
-        save_app_config(config)
+        save_config(&app, config)
     } else {
         Err("Repository not found".to_string())
     }
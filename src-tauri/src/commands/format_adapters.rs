@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::agent_config::RuleFormat;
+use crate::commands::frontmatter;
+
+const SUPPORTED_FORMATS: [&str; 3] = ["cursor_mdc", "windsurf_rule", "copilot_instructions"];
+
+// 原生规则文件与来源 skill 的映射记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NativeRuleEntry {
+    pub path: String, // 生成的规则文件 (绝对路径)
+    pub agent_id: String,
+    pub format: String,
+    pub skill_name: String,
+    pub plugin_name: String,
+    pub scope: String, // "global" | "project"
+    pub project_path: Option<String>,
+    pub source_path: String,
+    pub installed_at: String,
+}
+
+// native-rules.json 文件格式
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NativeRulesFile {
+    pub version: String,
+    pub entries: Vec<NativeRuleEntry>,
+    pub last_updated: String,
+}
+
+pub fn is_supported_format(kind: &str) -> bool {
+    SUPPORTED_FORMATS.contains(&kind)
+}
+
+/// Convert the skill at `skill_dir` into the agent's native rule format, write it
+/// into `rules_dir` and record the mapping so scans can find it again.
+pub fn install_native_rule(
+    format: &RuleFormat,
+    skill_dir: &Path,
+    rules_dir: &Path,
+    agent_id: &str,
+    plugin_name: &str,
+    scope: &str,
+    project_path: Option<String>,
+) -> Result<PathBuf, String> {
+    let content = fs::read_to_string(skill_dir.join("SKILL.md"))
+        .map_err(|e| format!("Failed to read SKILL.md: {}", e))?;
+
+    let folder_name = skill_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid skill path name")?
+        .to_string();
+
+    let (file_name, rendered) = render(&format.kind, &folder_name, &content, skill_dir)?;
+
+    fs::create_dir_all(rules_dir).map_err(|e| format!("Failed to create dir ({})", e))?;
+    let target = rules_dir.join(file_name);
+    check_target(&target, &load_native_rules()?.entries, plugin_name, &folder_name)?;
+    fs::write(&target, rendered).map_err(|e| format!("Failed to write rule file ({})", e))?;
+
+    record_entry(NativeRuleEntry {
+        path: target.to_string_lossy().to_string(),
+        agent_id: agent_id.to_string(),
+        format: format.kind.clone(),
+        skill_name: folder_name,
+        plugin_name: plugin_name.to_string(),
+        scope: scope.to_string(),
+        project_path,
+        source_path: skill_dir.to_string_lossy().to_string(),
+        installed_at: chrono::Utc::now().to_rfc3339(),
+    })?;
+
+    Ok(target)
+}
+
+/// Only overwrite a rule file that this plugin generated for this skill before.
+/// Hand-written rules and rules generated for other plugins are left alone.
+fn check_target(target: &Path, entries: &[NativeRuleEntry], plugin_name: &str, skill_name: &str) -> Result<(), String> {
+    if fs::symlink_metadata(target).is_err() {
+        return Ok(());
+    }
+    let path = target.to_string_lossy();
+    match entries.iter().find(|e| e.path == path) {
+        Some(e) if e.plugin_name == plugin_name && e.skill_name == skill_name => Ok(()),
+        Some(e) => Err(format!(
+            "{} already holds skill '{}' from '{}'; uninstall it first",
+            target.display(),
+            e.skill_name,
+            e.plugin_name
+        )),
+        None => Err(format!("{} already exists and was not generated by Skillbox Studio", target.display())),
+    }
+}
+
+/// Render a SKILL.md into `(file name, file content)` for the given native format.
+fn render(kind: &str, folder_name: &str, content: &str, skill_dir: &Path) -> Result<(String, String), String> {
+    let parsed = frontmatter::parse(content).map_err(|e| format!("Invalid SKILL.md: {}", e))?;
+    let name = parsed.name().unwrap_or(folder_name).to_string();
+    let description = parsed.description().unwrap_or_default().trim().to_string();
+    let body = parsed.body;
+
+    // Relative links (references/, scripts/) would break once the body leaves the skill folder
+    let note = format!(
+        "<!-- Generated by Skillbox Studio from skill '{}' ({}). Relative paths refer to that folder. -->\n\n",
+        name,
+        skill_dir.display()
+    );
+
+    match kind {
+        "cursor_mdc" => Ok((
+            format!("{}.mdc", folder_name),
+            format!(
+                "---\ndescription: {}\nglobs:\nalwaysApply: false\n---\n{}{}",
+                yaml_scalar(&description),
+                note,
+                body
+            ),
+        )),
+        "windsurf_rule" => Ok((
+            format!("{}.md", folder_name),
+            format!(
+                "---\ntrigger: model_decision\ndescription: {}\n---\n{}{}",
+                yaml_scalar(&description),
+                note,
+                body
+            ),
+        )),
+        "copilot_instructions" => Ok((
+            format!("{}.instructions.md", folder_name),
+            format!("---\ndescription: {}\n---\n{}{}", yaml_scalar(&description), note, body),
+        )),
+        other => Err(format!("Unsupported rule format: {}", other)),
+    }
+}
+
+fn yaml_scalar(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// 获取映射文件路径
+fn get_native_rules_path() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
+    let config_dir = home_dir.join(".skillbox-studio");
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+    }
+    Ok(config_dir.join("native-rules.json"))
+}
+
+pub fn load_native_rules() -> Result<NativeRulesFile, String> {
+    let path = get_native_rules_path()?;
+    if !path.exists() {
+        return Ok(NativeRulesFile {
+            version: "1.0".to_string(),
+            entries: Vec::new(),
+            last_updated: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read native rules index: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse native rules index: {}", e))
+}
+
+fn save_native_rules(file: &NativeRulesFile) -> Result<(), String> {
+    let path = get_native_rules_path()?;
+    let content = serde_json::to_string_pretty(file)
+        .map_err(|e| format!("Failed to serialize native rules index: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("Failed to write native rules index: {}", e))
+}
+
+fn record_entry(entry: NativeRuleEntry) -> Result<(), String> {
+    let mut file = load_native_rules()?;
+    // One entry per generated file; reinstalling replaces the old mapping
+    file.entries.retain(|e| e.path != entry.path);
+    file.entries.push(entry);
+    file.last_updated = chrono::Utc::now().to_rfc3339();
+    save_native_rules(&file)
+}
+
+/// Mappings whose generated file still exists. Entries for files deleted by hand are dropped.
+pub fn existing_native_rules() -> Vec<NativeRuleEntry> {
+    let Ok(mut file) = load_native_rules() else {
+        return Vec::new();
+    };
+    let before = file.entries.len();
+    file.entries.retain(|e| Path::new(&e.path).is_file());
+    if file.entries.len() != before {
+        file.last_updated = chrono::Utc::now().to_rfc3339();
+        let _ = save_native_rules(&file);
+    }
+    file.entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SKILL: &str = "---\nname: pdf-tools\ndescription: \"Work with PDF files\"\nmetadata:\n  author: me\n---\n\n# PDF Tools\n\nSee references/api.md\n";
+
+    #[test]
+    fn test_render_cursor_mdc() {
+        let (name, content) = render("cursor_mdc", "pdf", SKILL, Path::new("/skills/pdf")).unwrap();
+        assert_eq!(name, "pdf.mdc");
+        assert!(content.starts_with("---\ndescription: \"Work with PDF files\"\nglobs:\nalwaysApply: false\n---\n"));
+        assert!(content.contains("# PDF Tools"));
+    }
+
+    fn entry(path: &Path, plugin_name: &str) -> NativeRuleEntry {
+        NativeRuleEntry {
+            path: path.to_string_lossy().to_string(),
+            agent_id: "cursor".to_string(),
+            format: "cursor_mdc".to_string(),
+            skill_name: "pdf".to_string(),
+            plugin_name: plugin_name.to_string(),
+            scope: "global".to_string(),
+            project_path: None,
+            source_path: "/skills/pdf".to_string(),
+            installed_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_existing_rule_files_are_not_clobbered() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let target = dir.path().join("pdf.mdc");
+        assert!(check_target(&target, &[], "docs", "pdf").is_ok());
+
+        fs::write(&target, "hand-written").unwrap();
+        let err = check_target(&target, &[], "docs", "pdf").unwrap_err();
+        assert!(err.contains("not generated by Skillbox Studio"));
+
+        let owned = [entry(&target, "docs")];
+        assert!(check_target(&target, &owned, "docs", "pdf").is_ok());
+        let err = check_target(&target, &owned, "office", "pdf").unwrap_err();
+        assert!(err.contains("'docs'"));
+    }
+
+    #[test]
+    fn test_render_copilot_instructions() {
+        let (name, _) = render("copilot_instructions", "pdf", SKILL, Path::new("/skills/pdf")).unwrap();
+        assert_eq!(name, "pdf.instructions.md");
+        assert!(render("unknown", "pdf", SKILL, Path::new("/skills/pdf")).is_err());
+    }
+}
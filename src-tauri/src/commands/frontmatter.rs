@@ -0,0 +1,314 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// SKILL.md frontmatter 中的已知字段
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SkillFrontmatter {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub allowed_tools: Vec<String>, // `allowed-tools`, written as a list or a space/comma separated string
+    pub version: Option<String>,
+    pub metadata: BTreeMap<String, JsonValue>, // the nested `metadata:` map
+    pub extra: BTreeMap<String, JsonValue>, // any other top-level key
+}
+
+// 解析后的 SKILL.md
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ParsedSkillMd {
+    pub frontmatter: Option<SkillFrontmatter>, // None: the file doesn't start with `---`
+    pub keys: Vec<String>, // top-level keys in file order
+    pub key_lines: BTreeMap<String, usize>, // 1-based line of each top-level key
+    pub raw_frontmatter: String, // YAML between the `---` lines, as written
+    pub body: String,
+    pub body_line: usize, // 1-based line where the body starts
+}
+
+impl ParsedSkillMd {
+    pub fn name(&self) -> Option<&str> {
+        self.frontmatter.as_ref()?.name.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.frontmatter.as_ref()?.description.as_deref()
+    }
+}
+
+// 解析错误的类别
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum FrontmatterErrorKind {
+    Read,
+    Unterminated, // opening `---` without a closing one
+    InvalidYaml,
+    InvalidField, // valid YAML, but a known field has the wrong type
+}
+
+// 解析错误，行列号相对整个 SKILL.md (1-based)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FrontmatterError {
+    pub kind: FrontmatterErrorKind,
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl std::fmt::Display for FrontmatterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(col)) => write!(f, "{} (line {}, column {})", self.message, line, col),
+            (Some(line), None) => write!(f, "{} (line {})", self.message, line),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+fn error(kind: FrontmatterErrorKind, message: impl Into<String>, line: Option<usize>) -> FrontmatterError {
+    FrontmatterError { kind, message: message.into(), line, column: None }
+}
+
+/// Scalars (strings, numbers, booleans) as text, so `version: 1.0` still reads as "1.0"
+fn scalar(value: &YamlValue) -> Option<String> {
+    match value {
+        YamlValue::String(s) => Some(s.clone()),
+        YamlValue::Number(n) => Some(n.to_string()),
+        YamlValue::Bool(b) => Some(b.to_string()),
+        YamlValue::Tagged(t) => scalar(&t.value),
+        _ => None,
+    }
+}
+
+fn to_json(value: &YamlValue, line: Option<usize>) -> Result<JsonValue, FrontmatterError> {
+    serde_json::to_value(value).map_err(|e| error(FrontmatterErrorKind::InvalidField, format!("Unsupported YAML value: {}", e), line))
+}
+
+fn field_error(key: &str, expected: &str, line: Option<usize>) -> FrontmatterError {
+    error(FrontmatterErrorKind::InvalidField, format!("'{}' must be {}", key, expected), line)
+}
+
+/// Lenient reading of frontmatter that isn't valid YAML: every line must be a top-level
+/// `key: rest of line`, and the rest is taken as a plain string (surrounding quotes removed).
+/// Anything nested or multi-line gives None, so the YAML error is reported instead.
+fn plain_scalars(yaml: &str) -> Option<YamlValue> {
+    let mut mapping = serde_yaml::Mapping::new();
+    for line in yaml.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with([' ', '\t', '-']) {
+            return None;
+        }
+        let (key, rest) = line.split_once(':')?;
+        let (key, rest) = (key.trim(), rest.trim());
+        if key.is_empty() || rest.is_empty() || rest.starts_with(['|', '>', '[', '{']) {
+            return None;
+        }
+        let unquoted = [('"', '"'), ('\'', '\'')]
+            .iter()
+            .find_map(|(open, close)| rest.strip_prefix(*open)?.strip_suffix(*close))
+            .unwrap_or(rest);
+        mapping.insert(YamlValue::String(key.to_string()), YamlValue::String(unquoted.to_string()));
+    }
+    Some(YamlValue::Mapping(mapping))
+}
+
+/// Parse a whole SKILL.md. A file without frontmatter is not an error (`frontmatter` is None);
+/// an unterminated block, invalid YAML or a wrongly typed known field is.
+pub fn parse(content: &str) -> Result<ParsedSkillMd, FrontmatterError> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+
+    // Byte offset just past each line, to cut the body out without re-joining lines
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        offset += line.len();
+        lines.push((line.trim_end_matches(['\n', '\r']), offset));
+    }
+
+    if lines.first().map(|(l, _)| l.trim_end()) != Some("---") {
+        return Ok(ParsedSkillMd {
+            frontmatter: None,
+            keys: Vec::new(),
+            key_lines: BTreeMap::new(),
+            raw_frontmatter: String::new(),
+            body: content.to_string(),
+            body_line: 1,
+        });
+    }
+
+    let close = lines
+        .iter()
+        .skip(1)
+        .position(|(l, _)| matches!(l.trim_end(), "---" | "..."))
+        .map(|i| i + 1)
+        .ok_or_else(|| error(FrontmatterErrorKind::Unterminated, "Frontmatter is never closed with '---'", Some(1)))?;
+
+    let yaml_start = lines[0].1;
+    let yaml_end = lines[close - 1].1.max(yaml_start);
+    let yaml = &content[yaml_start..yaml_end];
+    let body = &content[lines[close].1..];
+
+    // Top-level keys and where they are, for errors and for the linter
+    let mut key_lines = BTreeMap::new();
+    for (i, (line, _)) in lines[1..close].iter().enumerate() {
+        if line.starts_with([' ', '\t', '#', '-']) {
+            continue;
+        }
+        if let Some((key, _)) = line.split_once(':') {
+            let key = key.trim().trim_matches(['"', '\'']);
+            if !key.is_empty() {
+                key_lines.entry(key.to_string()).or_insert(i + 2);
+            }
+        }
+    }
+    let line_of = |key: &str| key_lines.get(key).copied();
+
+    let value: YamlValue = if yaml.trim().is_empty() {
+        YamlValue::Mapping(Default::default())
+    } else {
+        match serde_yaml::from_str(yaml) {
+            Ok(value) => value,
+            // Agents read `description: Use when: ...` fine even though it isn't valid YAML
+            Err(e) => plain_scalars(yaml).ok_or_else(|| FrontmatterError {
+                kind: FrontmatterErrorKind::InvalidYaml,
+                message: format!("Invalid YAML frontmatter: {}", e.to_string().split(" at line ").next().unwrap_or_default()),
+                // +1 for the opening `---`
+                line: e.location().map(|l| l.line() + 1),
+                column: e.location().map(|l| l.column()),
+            })?,
+        }
+    };
+    let mapping = match value {
+        YamlValue::Mapping(m) => m,
+        YamlValue::Null => Default::default(),
+        _ => return Err(error(FrontmatterErrorKind::InvalidYaml, "Frontmatter must be a map of 'key: value' pairs", Some(2))),
+    };
+
+    let mut fm = SkillFrontmatter::default();
+    let mut keys = Vec::new();
+    for (key, value) in &mapping {
+        let Some(key) = scalar(key) else {
+            return Err(error(FrontmatterErrorKind::InvalidYaml, "Frontmatter keys must be plain strings", None));
+        };
+        let line = line_of(&key);
+        match key.as_str() {
+            "name" | "description" | "license" | "version" if value.is_null() => {}
+            "name" => fm.name = Some(scalar(value).ok_or_else(|| field_error(&key, "a string", line))?),
+            "description" => fm.description = Some(scalar(value).ok_or_else(|| field_error(&key, "a string", line))?),
+            "license" => fm.license = Some(scalar(value).ok_or_else(|| field_error(&key, "a string", line))?),
+            "version" => fm.version = Some(scalar(value).ok_or_else(|| field_error(&key, "a string", line))?),
+            "allowed-tools" => {
+                fm.allowed_tools = match value {
+                    YamlValue::Null => Vec::new(),
+                    YamlValue::Sequence(items) => items
+                        .iter()
+                        .map(|v| scalar(v).ok_or_else(|| field_error(&key, "a list of tool names", line)))
+                        .collect::<Result<_, _>>()?,
+                    other => scalar(other)
+                        .ok_or_else(|| field_error(&key, "a list of tool names", line))?
+                        .split([' ', ','])
+                        .filter(|t| !t.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                }
+            }
+            "metadata" => match value {
+                YamlValue::Null => {}
+                YamlValue::Mapping(map) => {
+                    for (k, v) in map {
+                        let k = scalar(k).ok_or_else(|| field_error(&key, "a map with string keys", line))?;
+                        fm.metadata.insert(k, to_json(v, line)?);
+                    }
+                }
+                _ => return Err(field_error(&key, "a map", line)),
+            },
+            _ => {
+                fm.extra.insert(key.clone(), to_json(value, line)?);
+            }
+        }
+        keys.push(key);
+    }
+
+    // Blank lines right after the closing `---` are not part of the body
+    let trimmed = body.trim_start_matches(['\r', '\n']);
+    let skipped = body[..body.len() - trimmed.len()].matches('\n').count();
+    Ok(ParsedSkillMd {
+        frontmatter: Some(fm),
+        keys,
+        key_lines,
+        raw_frontmatter: yaml.to_string(),
+        body: trimmed.to_string(),
+        body_line: close + 2 + skipped,
+    })
+}
+
+/// Read and parse `path` (a SKILL.md file)
+pub fn parse_file(path: &Path) -> Result<ParsedSkillMd, FrontmatterError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| error(FrontmatterErrorKind::Read, format!("Failed to read {}: {}", path.display(), e), None))?;
+    parse(&content)
+}
+
+/// 解析 skill 目录中 SKILL.md 的 frontmatter
+#[tauri::command]
+pub fn read_skill_frontmatter(skill_path: String) -> Result<ParsedSkillMd, String> {
+    let path = Path::new(&skill_path);
+    let file = if path.is_dir() { path.join("SKILL.md") } else { path.to_path_buf() };
+    parse_file(&file).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_scalars_lists_and_metadata() {
+        let content = "---\nname: \"pdf-tools\"\ndescription: >\n  Work with PDFs:\n  fill forms.\nallowed-tools: [Read, Bash]\nversion: 1.0\nmetadata:\n  author: me\n  tags: [pdf, docs]\nhomepage: https://example.com\n---\n\n# PDF\n";
+        let parsed = parse(content).unwrap();
+        let fm = parsed.frontmatter.clone().unwrap();
+        assert_eq!(fm.name.as_deref(), Some("pdf-tools"));
+        assert_eq!(fm.description.as_deref(), Some("Work with PDFs: fill forms.\n"));
+        assert_eq!(fm.allowed_tools, vec!["Read", "Bash"]);
+        assert_eq!(fm.version.as_deref(), Some("1.0"));
+        assert_eq!(fm.metadata["tags"], serde_json::json!(["pdf", "docs"]));
+        assert!(fm.extra.contains_key("homepage"));
+        assert_eq!(parsed.key_lines["description"], 3);
+        assert_eq!(parsed.body, "# PDF\n");
+        assert_eq!(parsed.body_line, 14);
+
+        let tools = parse("---\nname: a\nallowed-tools: Read Grep, Glob\n---\n").unwrap();
+        assert_eq!(tools.frontmatter.unwrap().allowed_tools, vec!["Read", "Grep", "Glob"]);
+    }
+
+    #[test]
+    fn test_errors_carry_file_lines() {
+        let err = parse("---\nname: a\ndescription: [unclosed\n---\nbody\n").unwrap_err();
+        assert!(err.line.is_some_and(|l| l >= 3), "{:?}", err);
+
+        let err = parse("---\nname: a\n").unwrap_err();
+        assert_eq!(err.line, Some(1));
+
+        let err = parse("---\nname: a\nmetadata: 3\n---\n").unwrap_err();
+        assert_eq!(err.line, Some(3));
+
+        assert!(parse("# no frontmatter\n").unwrap().frontmatter.is_none());
+    }
+
+    #[test]
+    fn test_unquoted_colons_fall_back_to_plain_lines() {
+        let parsed = parse("---\nname: pdf\ndescription: Use when: the user asks for a PDF\nversion: 2\n---\nbody\n").unwrap();
+        let fm = parsed.frontmatter.unwrap();
+        assert_eq!(fm.name.as_deref(), Some("pdf"));
+        assert_eq!(fm.description.as_deref(), Some("Use when: the user asks for a PDF"));
+        assert_eq!(fm.version.as_deref(), Some("2"));
+        assert_eq!(parsed.keys, vec!["name", "description", "version"]);
+
+        // Nested blocks can't be read line by line, so the YAML error stands
+        let err = parse("---\nname: a: b\nmetadata:\n  x: 1\n---\n").unwrap_err();
+        assert_eq!(err.kind, FrontmatterErrorKind::InvalidYaml);
+        assert_eq!(parse("---\nname: a\n").unwrap_err().kind, FrontmatterErrorKind::Unterminated);
+    }
+}
@@ -1,97 +1,638 @@
-use std::process::Command;
-use std::path::Path;
-use crate::types::{RepositoryInfo, ValidationResult};
-use crate::commands::marketplace::MarketplaceData;
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{Cred, CredentialType, ErrorClass, ErrorCode, FetchOptions, FetchPrune, Oid, RemoteCallbacks, Repository};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Emitter;
+use crate::types::{RepositoryInfo, UpdateResult, ValidationResult};
+use crate::commands::config_manager;
+use crate::commands::credential_store::{self, RepoCredential};
+use crate::commands::marketplace::{MarketplaceData, Plugin};
+use crate::commands::refresh_scheduler;
 
-pub async fn clone_repository(url: &str, dest: &Path) -> Result<(), String> {
+/// Event emitted while objects are downloaded during a clone or fetch
+pub const GIT_PROGRESS_EVENT: &str = "git-progress";
+
+// libgit2 calls the credential callback again after every rejected attempt
+const MAX_AUTH_ATTEMPTS: u32 = 3;
+
+#[derive(Debug)]
+pub enum GitError {
+    Io(String),
+    NotARepository(PathBuf),
+    DetachedHead,
+    NoUpstream(String), // branch name
+    Diverged,
+    LocalChanges,
+    Auth(String),
+    Network(String),
+    Git(git2::Error),
+    Task(String), // the blocking task panicked or was cancelled
+}
+
+impl std::fmt::Display for GitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitError::Io(e) => write!(f, "{}", e),
+            GitError::NotARepository(path) => write!(f, "{} is not a git clone", path.display()),
+            GitError::DetachedHead => write!(f, "The clone is not on a branch (detached HEAD)"),
+            GitError::NoUpstream(branch) => write!(f, "Branch '{}' has no upstream to update from", branch),
+            GitError::Diverged => write!(f, "Cannot fast-forward, the local clone has diverged from its remote"),
+            GitError::LocalChanges => write!(f, "Local changes in the clone would be overwritten by the update"),
+            GitError::Auth(e) => write!(f, "Authentication failed: {}", e),
+            GitError::Network(e) => write!(f, "Network error: {}", e),
+            GitError::Git(e) => write!(f, "Git error: {}", e.message()),
+            GitError::Task(e) => write!(f, "Git task failed: {}", e),
+        }
+    }
+}
+
+impl From<git2::Error> for GitError {
+    fn from(e: git2::Error) -> Self {
+        match (e.code(), e.class()) {
+            (ErrorCode::Auth, _) => GitError::Auth(e.message().to_string()),
+            // Other checkout failures (permissions, locked files...) keep their own message
+            (ErrorCode::Conflict, _) => GitError::LocalChanges,
+            (_, ErrorClass::Net | ErrorClass::Http | ErrorClass::Ssl | ErrorClass::Ssh) => {
+                GitError::Network(e.message().to_string())
+            }
+            _ => GitError::Git(e),
+        }
+    }
+}
+
+impl From<GitError> for String {
+    fn from(e: GitError) -> Self {
+        e.to_string()
+    }
+}
+
+// 传输进度（事件负载）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GitProgress {
+    pub repo_id: String,
+    pub operation: String, // "clone" | "fetch"
+    pub received_objects: usize,
+    pub total_objects: usize,
+    pub indexed_deltas: usize,
+    pub total_deltas: usize,
+    pub received_bytes: usize,
+}
+
+// 本地克隆状态（不联网）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepositoryStatus {
+    pub is_git: bool,
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: usize, // as of the last fetch
+    pub behind: usize,
+    pub dirty: bool, // tracked files modified locally
+}
+
+// 一次快进的结果
+pub struct FastForward {
+    pub old: Oid,
+    pub new: Oid,
+    pub commits: Vec<String>, // "<short hash> <subject>", newest first
+    pub changed_files: Vec<String>,
+}
+
+type ProgressFn<'a> = &'a mut (dyn FnMut(git2::Progress<'_>) -> bool + Send);
+
+/// Credentials stored for the repository first, then the SSH agent, the git credential helper
+/// and the platform default
+fn credentials_callback(
+    credential: Option<RepoCredential>,
+) -> impl FnMut(&str, Option<&str>, CredentialType) -> Result<Cred, git2::Error> {
+    let mut attempts = 0;
+    move |url, username, allowed| {
+        attempts += 1;
+        if attempts > MAX_AUTH_ATTEMPTS {
+            return Err(git2::Error::new(ErrorCode::Auth, ErrorClass::Callback, "no valid credentials"));
+        }
+        let user = credential.as_ref().and_then(|c| c.username.as_deref()).or(username);
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(user.unwrap_or("git"));
+        }
+        if let Some(c) = &credential {
+            // Tokens are never sent to another host, e.g. after a redirect
+            let same_host = c.host.is_some() && c.host == credential_store::url_host(url);
+            if c.kind == "token" && same_host && allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+                let token = c.token.as_deref().unwrap_or_default();
+                return Cred::userpass_plaintext(user.unwrap_or("x-access-token"), token);
+            }
+            let use_key = c.kind == "ssh" && allowed.contains(CredentialType::SSH_KEY);
+            if let Some(key) = c.ssh_key_path.as_ref().filter(|_| use_key) {
+                return Cred::ssh_key(user.unwrap_or("git"), None, Path::new(key), c.ssh_passphrase.as_deref());
+            }
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(user.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Ok(config) = git2::Config::open_default() {
+                if let Ok(cred) = Cred::credential_helper(&config, url, username) {
+                    return Ok(cred);
+                }
+            }
+        }
+        if allowed.contains(CredentialType::DEFAULT) {
+            return Cred::default();
+        }
+        Err(git2::Error::new(ErrorCode::Auth, ErrorClass::Callback, "no credentials available"))
+    }
+}
+
+fn fetch_options<'a>(credential: Option<RepoCredential>, progress: ProgressFn<'a>) -> FetchOptions<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(credentials_callback(credential));
+    callbacks.transfer_progress(progress);
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(callbacks);
+    options
+}
+
+/// Progress callback that forwards to the frontend, once per percent
+pub fn progress_emitter(
+    app: &tauri::AppHandle,
+    repo_id: &str,
+    operation: &str,
+) -> impl FnMut(git2::Progress<'_>) -> bool + Send + 'static {
+    let app = app.clone();
+    let (repo_id, operation) = (repo_id.to_string(), operation.to_string());
+    let mut last_percent = None;
+    move |p| {
+        let total = p.total_objects() + p.total_deltas();
+        let percent = ((p.received_objects() + p.indexed_deltas()) * 100).checked_div(total).unwrap_or(0);
+        if last_percent != Some(percent) {
+            last_percent = Some(percent);
+            let event = GitProgress {
+                repo_id: repo_id.clone(),
+                operation: operation.clone(),
+                received_objects: p.received_objects(),
+                total_objects: p.total_objects(),
+                indexed_deltas: p.indexed_deltas(),
+                total_deltas: p.total_deltas(),
+                received_bytes: p.received_bytes(),
+            };
+            let _ = app.emit(GIT_PROGRESS_EVENT, &event);
+        }
+        true
+    }
+}
+
+/// Run blocking git work off the async runtime
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, GitError> + Send + 'static,
+) -> Result<T, GitError> {
+    tauri::async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|e| GitError::Task(e.to_string()))?
+}
+
+pub fn clone_repository(
+    url: &str,
+    dest: &Path,
+    credential: Option<RepoCredential>,
+    progress: ProgressFn<'_>,
+) -> Result<(), GitError> {
     // Ensure parent directory exists
     if let Some(parent) = dest.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        std::fs::create_dir_all(parent).map_err(|e| GitError::Io(format!("Failed to create directory: {}", e)))?;
     }
+    RepoBuilder::new().fetch_options(fetch_options(credential, progress)).clone(url, dest)?;
+    Ok(())
+}
 
-    let output = Command::new("git")
-        .args(&["clone", url, dest.to_str().unwrap()])
-        .output()
-        .map_err(|e| format!("Failed to execute git clone: {}", e))?;
+fn open(path: &Path) -> Result<Repository, GitError> {
+    Repository::open(path).map_err(|_| GitError::NotARepository(path.to_path_buf()))
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Git clone failed: {}", stderr));
+/// Fetch the upstream of the current branch and fast-forward to it
+pub fn fast_forward(
+    path: &Path,
+    credential: Option<RepoCredential>,
+    progress: ProgressFn<'_>,
+) -> Result<FastForward, GitError> {
+    let repo = open(path)?;
+    let head = repo.head()?;
+    if !head.is_branch() {
+        return Err(GitError::DetachedHead);
     }
+    let branch_ref = head.name().ok_or(GitError::DetachedHead)?.to_string();
+    let branch = head.shorthand().unwrap_or_default().to_string();
+    let old = head.peel_to_commit()?.id();
 
-    Ok(())
+    let no_upstream = |_| GitError::NoUpstream(branch.clone());
+    let remote_name = repo.branch_upstream_remote(&branch_ref).map_err(no_upstream)?;
+    let upstream_ref = repo.branch_upstream_name(&branch_ref).map_err(no_upstream)?;
+    let remote_name = remote_name.as_str().ok_or(GitError::NoUpstream(branch.clone()))?;
+    let upstream_ref = upstream_ref.as_str().ok_or(GitError::NoUpstream(branch.clone()))?;
+
+    let mut options = fetch_options(credential, progress);
+    options.prune(FetchPrune::On);
+    repo.find_remote(remote_name)?.fetch(&[] as &[&str], Some(&mut options), None)?;
+
+    let new = repo.refname_to_id(upstream_ref)?;
+    if new == old {
+        return Ok(FastForward { old, new, commits: Vec::new(), changed_files: Vec::new() });
+    }
+    let (ahead, _) = repo.graph_ahead_behind(old, new)?;
+    if ahead > 0 {
+        return Err(GitError::Diverged);
+    }
+
+    // Safe checkout refuses to overwrite local modifications
+    let target = repo.find_commit(new)?;
+    repo.checkout_tree(target.as_object(), Some(CheckoutBuilder::new().safe()))?;
+    repo.find_reference(&branch_ref)?.set_target(new, "skillbox: fast-forward")?;
+
+    let mut walk = repo.revwalk()?;
+    walk.push(new)?;
+    walk.hide(old)?;
+    let mut commits = Vec::new();
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        let short = commit.as_object().short_id()?;
+        commits.push(format!("{} {}", short.as_str().unwrap_or_default(), commit.summary().unwrap_or_default()));
+    }
+
+    let old_tree = repo.find_commit(old)?.tree()?;
+    let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&target.tree()?), None)?;
+    let mut changed_files = Vec::new();
+    for delta in diff.deltas() {
+        for file in [delta.old_file().path(), delta.new_file().path()].into_iter().flatten() {
+            let file = file.to_string_lossy().replace('\\', "/");
+            if !changed_files.contains(&file) {
+                changed_files.push(file);
+            }
+        }
+    }
+
+    Ok(FastForward { old, new, commits, changed_files })
 }
 
-#[allow(dead_code)]
-pub async fn pull_repository(path: &Path) -> Result<String, String> {
-    let output = Command::new("git")
-        .args(&["-C", path.to_str().unwrap(), "pull"])
-        .output()
-        .map_err(|e| format!("Failed to execute git pull: {}", e))?;
+/// Branch, upstream and local changes of a clone, without touching the network
+pub fn repository_status(path: &Path) -> Result<RepositoryStatus, GitError> {
+    let Ok(repo) = Repository::open(path) else {
+        return Ok(RepositoryStatus {
+            is_git: false,
+            branch: None,
+            head: None,
+            upstream: None,
+            ahead: 0,
+            behind: 0,
+            dirty: false,
+        });
+    };
+
+    let head = repo.head().ok();
+    let branch = head.as_ref().filter(|h| h.is_branch()).and_then(|h| h.shorthand()).map(str::to_string);
+    let head_oid = head.as_ref().and_then(|h| h.target());
+    let upstream_ref = head
+        .as_ref()
+        .and_then(|h| h.name())
+        .and_then(|name| repo.branch_upstream_name(name).ok())
+        .and_then(|buf| buf.as_str().map(str::to_string));
+    let (ahead, behind) = match (head_oid, upstream_ref.as_deref().and_then(|r| repo.refname_to_id(r).ok())) {
+        (Some(local), Some(remote)) => repo.graph_ahead_behind(local, remote)?,
+        _ => (0, 0),
+    };
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Git pull failed: {}", stderr));
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(false);
+    let dirty = !repo.statuses(Some(&mut options))?.is_empty();
+
+    Ok(RepositoryStatus {
+        is_git: true,
+        branch,
+        head: head_oid.map(|o| o.to_string()),
+        upstream: upstream_ref.map(|r| r.trim_start_matches("refs/remotes/").to_string()),
+        ahead,
+        behind,
+        dirty,
+    })
+}
+
+fn read_plugins(repo_path: &Path) -> Vec<Plugin> {
+    std::fs::read_to_string(repo_path.join(".claude-plugin").join("marketplace.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<MarketplaceData>(&c).ok())
+        .map(|d| d.plugins)
+        .unwrap_or_default()
+}
+
+/// (added, removed, changed) plugin names between two marketplace.json versions.
+/// A plugin also counts as changed when a file below one of its skills changed.
+fn plugin_changes(before: &[Plugin], after: &[Plugin], changed_files: &[String]) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let find = |list: &[Plugin], name: &str| list.iter().find(|p| p.name == name).cloned();
+    let as_json = |p: &Plugin| serde_json::to_string(p).unwrap_or_default();
+
+    let added = after.iter().filter(|p| find(before, &p.name).is_none()).map(|p| p.name.clone()).collect();
+    let removed = before.iter().filter(|p| find(after, &p.name).is_none()).map(|p| p.name.clone()).collect();
+    let changed = after
+        .iter()
+        .filter(|p| {
+            let Some(old) = find(before, &p.name) else {
+                return false;
+            };
+            as_json(&old) != as_json(p)
+                || p.skills.iter().any(|skill| {
+                    let prefix = format!("{}/", skill.trim_start_matches("./").trim_end_matches('/'));
+                    changed_files.iter().any(|f| f.starts_with(&prefix))
+                })
+        })
+        .map(|p| p.name.clone())
+        .collect();
+    (added, removed, changed)
+}
+
+pub async fn update_repository(app: &tauri::AppHandle, repo: &RepositoryInfo) -> Result<UpdateResult, String> {
+    let path = PathBuf::from(&repo.local_path);
+    if !path.join(".git").exists() {
+        return Err(GitError::NotARepository(path).into());
     }
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    let credential = match repo.auth_type.as_str() {
+        "public" => None,
+        _ => credential_store::load(&repo.id)?,
+    };
+    let redact = |e: GitError| credential.as_ref().map_or(e.to_string(), |c| c.redact(&e.to_string()));
+
+    let before = read_plugins(&path);
+    let mut progress = progress_emitter(app, &repo.id, "fetch");
+    let (repo_path, fetch_credential) = (path.clone(), credential.clone());
+    let ff = run_blocking(move || fast_forward(&repo_path, fetch_credential, &mut progress))
+        .await
+        .map_err(redact)?;
+    let (added, removed, changed) = plugin_changes(&before, &read_plugins(&path), &ff.changed_files);
+
+    let changes = if ff.commits.is_empty() {
+        "Already up to date".to_string()
+    } else {
+        format!(
+            "{} new commit(s): {} plugin(s) added, {} removed, {} changed",
+            ff.commits.len(),
+            added.len(),
+            removed.len(),
+            changed.len()
+        )
+    };
+    Ok(UpdateResult {
+        repo_id: repo.id.clone(),
+        repo_name: repo.name.clone(),
+        success: true,
+        error: None,
+        changes,
+        commits: ff.commits,
+        old_commit: Some(ff.old.to_string()),
+        new_commit: Some(ff.new.to_string()),
+        added_plugins: added,
+        removed_plugins: removed,
+        changed_plugins: changed,
+        last_updated: chrono::Utc::now().to_rfc3339(),
+    })
 }
 
+pub fn failed_update(repo: &RepositoryInfo, error: String) -> UpdateResult {
+    UpdateResult {
+        repo_id: repo.id.clone(),
+        repo_name: repo.name.clone(),
+        success: false,
+        changes: error.clone(),
+        error: Some(error),
+        commits: Vec::new(),
+        old_commit: None,
+        new_commit: None,
+        added_plugins: Vec::new(),
+        removed_plugins: Vec::new(),
+        changed_plugins: Vec::new(),
+        last_updated: repo.last_updated.clone(),
+    }
+}
+
+/// Store the new `last_updated` of every successfully updated repository
+pub fn record_updates(app: &tauri::AppHandle, results: &[UpdateResult]) -> Result<(), String> {
+    let mut config = config_manager::load_config(app)?;
+    for result in results.iter().filter(|r| r.success) {
+        if let Some(repo) = config.marketplace.repositories.iter_mut().find(|r| r.id == result.repo_id) {
+            repo.last_updated = result.last_updated.clone();
+        }
+    }
+    config_manager::save_config(app, config)
+}
+
+/// 拉取单个市场仓库（仅快进）并汇总变更
+#[tauri::command]
+pub async fn update_marketplace_repository(app: tauri::AppHandle, repo_id: String) -> Result<UpdateResult, String> {
+    let config = config_manager::load_config(&app)?;
+    let repo = config
+        .marketplace
+        .repositories
+        .into_iter()
+        .find(|r| r.id == repo_id)
+        .ok_or("Repository not found")?;
+
+    let _lock = refresh_scheduler::lock_updates()?;
+    let result = update_repository(&app, &repo).await?;
+    record_updates(&app, std::slice::from_ref(&result))?;
+    Ok(result)
+}
+
+/// 更新所有已启用的 git 仓库；单个仓库失败不会中断其余仓库
+#[tauri::command]
+pub async fn update_all_marketplace_repositories(app: tauri::AppHandle) -> Result<Vec<UpdateResult>, String> {
+    let config = config_manager::load_config(&app)?;
+    let _lock = refresh_scheduler::lock_updates()?;
+    let mut results = Vec::new();
+    for repo in config.marketplace.repositories.iter().filter(|r| r.enabled) {
+        if !Path::new(&repo.local_path).join(".git").exists() {
+            continue; // bundled or manually managed folders
+        }
+        results.push(update_repository(&app, repo).await.unwrap_or_else(|e| failed_update(repo, e)));
+    }
+    record_updates(&app, &results)?;
+    Ok(results)
+}
+
+/// 查看仓库本地克隆的分支、上游与改动状态
+#[tauri::command]
+pub fn get_repository_status(app: tauri::AppHandle, repo_id: String) -> Result<RepositoryStatus, String> {
+    let config = config_manager::load_config(&app)?;
+    let repo = config
+        .marketplace
+        .repositories
+        .iter()
+        .find(|r| r.id == repo_id)
+        .ok_or("Repository not found")?;
+    Ok(repository_status(Path::new(&repo.local_path))?)
+}
+
+/// Same repository regardless of scheme, case, trailing slash or `.git`
+/// (`https://github.com/a/b` and `git@github.com:a/b.git` match)
+pub fn repo_key(url: &str) -> String {
+    let trimmed = url.trim().trim_end_matches('/');
+    let trimmed = trimmed.strip_suffix(".git").unwrap_or(trimmed);
+    let path = match url::Url::parse(trimmed) {
+        Ok(parsed) if parsed.host_str().is_some() => parsed.path().to_string(),
+        _ => trimmed.split_once('@').and_then(|(_, rest)| rest.split_once(':')).map_or(String::new(), |(_, p)| p.to_string()),
+    };
+    match credential_store::url_host(trimmed) {
+        Some(host) => format!("{}/{}", host, path.trim_matches('/')).to_lowercase(),
+        None => trimmed.to_lowercase(), // local paths
+    }
+}
+
+fn repositories_dir() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
+    Ok(home_dir.join(".skillbox-studio").join("repositories"))
+}
+
+// URLs of repositories being added right now
+static ADDING: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// An add in progress. Dropping it before `commit` undoes every step taken so far,
+/// also when the command errors out early or its future is dropped.
+struct PendingAdd {
+    key: String,
+    repo_id: String,
+    dest: PathBuf,
+    credential_saved: bool,
+    committed: bool,
+}
+
+impl PendingAdd {
+    fn begin(key: String, repo_id: String, dest: PathBuf) -> Result<Self, String> {
+        let mut adding = ADDING.lock().map_err(|e| e.to_string())?;
+        if !adding.insert(key.clone()) {
+            return Err("This repository is already being added".to_string());
+        }
+        Ok(PendingAdd { key, repo_id, dest, credential_saved: false, committed: false })
+    }
+}
+
+impl Drop for PendingAdd {
+    fn drop(&mut self) {
+        if !self.committed {
+            if self.dest.exists() {
+                let _ = std::fs::remove_dir_all(&self.dest);
+            }
+            if self.credential_saved {
+                let _ = credential_store::remove(&self.repo_id);
+            }
+        }
+        if let Ok(mut adding) = ADDING.lock() {
+            adding.remove(&self.key);
+        }
+    }
+}
+
+fn ensure_not_configured(repositories: &[RepositoryInfo], key: &str) -> Result<(), String> {
+    match repositories.iter().find(|r| repo_key(&r.url) == key) {
+        Some(existing) => Err(format!("Repository already added as '{}'", existing.name)),
+        None => Ok(()),
+    }
+}
+
+/// 添加市场仓库：检查重复、克隆、校验、保存凭据与配置；任一步失败都会回滚
 #[tauri::command]
 pub async fn add_marketplace_repository(
+    app: tauri::AppHandle,
     url: String,
     name: String,
     auth_type: String,
-    _auth_token: Option<String> // Token handling to be added later if needed
+    username: Option<String>,
+    auth_token: Option<String>,
+    ssh_key_path: Option<String>,
 ) -> Result<RepositoryInfo, String> {
-    // Determine storage path
-    // For now, use a fixed directory under .skillbox-studio/repositories
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
+    let url = url.trim().to_string();
+    credential_store::check_url(&url)?;
+    let credential = RepoCredential::from_auth(&auth_type, &url, username, auth_token, ssh_key_path)?;
+
+    let key = repo_key(&url);
+    ensure_not_configured(&config_manager::load_config(&app)?.marketplace.repositories, &key)?;
+
     let repo_id = uuid::Uuid::new_v4().to_string();
-    let repo_dir_name = format!("repo-{}", repo_id);
-    let dest_path = home_dir.join(".skillbox-studio").join("repositories").join(&repo_dir_name);
+    let dest_path = repositories_dir()?.join(format!("repo-{}", repo_id));
+    let mut pending = PendingAdd::begin(key, repo_id.clone(), dest_path.clone())?;
 
     // Clone repo
-    clone_repository(&url, &dest_path).await?;
+    let mut progress = progress_emitter(&app, &repo_id, "clone");
+    let (clone_url, clone_dest, clone_credential) = (url.clone(), dest_path.clone(), credential.clone());
+    run_blocking(move || clone_repository(&clone_url, &clone_dest, clone_credential, &mut progress))
+        .await
+        .map_err(|e| credential.as_ref().map_or(e.to_string(), |c| c.redact(&e.to_string())))?;
 
-    // Validate
-    match validate_repo_path(&dest_path) {
-        Ok(_) => {},
-        Err(e) => {
-             // Cleanup if validation fails
-             let _ = std::fs::remove_dir_all(&dest_path);
-             return Err(format!("Invalid marketplace repository: {}", e));
-        }
+    validate_repo_path(&dest_path).map_err(|e| format!("Invalid marketplace repository: {}", e))?;
+
+    // Credentials live in the encrypted store, never in config.json
+    if let Some(credential) = credential {
+        credential_store::save(&repo_id, credential)?;
+        pending.credential_saved = true;
     }
-    
-    // Create Repo Info
+
     let info = RepositoryInfo {
         id: repo_id,
-        name: if name.is_empty() { "Custom Repository".to_string() } else { name },
+        name: if name.trim().is_empty() { "Custom Repository".to_string() } else { name.trim().to_string() },
         url,
         repo_type: "custom".to_string(),
         enabled: true,
         priority: 10, // Default low priority for new repos
-        local_path: dest_path.to_str().unwrap().to_string(),
+        local_path: dest_path.to_string_lossy().to_string(),
         last_updated: chrono::Utc::now().to_rfc3339(),
         auth_type,
+        pinned: false,
     };
 
-    // Note: We need to save this to config, but this command just returns the info. 
-    // The orchestration should probably happen in a "service" layer, or front end calls add_repo then save_config.
-    // However, to be atomic, we should probably inject config manager here. 
-    // For simplicity, let's assume the frontend will receive this and then call "update_marketplace_config" or similar,
-    // OR this command should be in config_manager or have access to it.
-    // Better design: separate "Git Manager" (pure git ops) from "commands" that bind to frontend.
-    // But since this is `src/commands/git_manager.rs`, let's make it a tauri command that DOES it all if possible.
-    // Ideally we update config here.
-    
-    // For now, let's just return the info. The frontend can add it to the list and save.
-    // actually, to be safe, let's keep it simple.
-    
+    // Re-read the config: it may have changed while cloning
+    let mut config = config_manager::load_config(&app)?;
+    ensure_not_configured(&config.marketplace.repositories, &pending.key)?;
+    config.marketplace.repositories.push(info.clone());
+    config_manager::save_config(&app, config)?;
+    pending.committed = true;
+
     Ok(info)
 }
 
+/// Remove clones under `~/.skillbox-studio/repositories` that no configured repository points at,
+/// left behind when the app quit in the middle of adding a repository.
+/// Nothing is removed unless config.json exists and parses: a missing or broken config
+/// would make every clone look orphaned.
+pub fn remove_orphan_clones(app: &tauri::AppHandle) -> Result<usize, String> {
+    if !config_manager::get_config_path()?.is_file() {
+        return Ok(0);
+    }
+    let config = config_manager::load_config(app)?;
+    let Ok(entries) = std::fs::read_dir(repositories_dir()?) else {
+        return Ok(0);
+    };
+    // Compared canonically, so `~/.skillbox-studio/../.skillbox-studio/...` or a linked home still match
+    let canonical = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    let in_use: HashSet<PathBuf> = config
+        .marketplace
+        .repositories
+        .iter()
+        .map(|r| canonical(Path::new(&r.local_path)))
+        .collect();
+    // A clone being added isn't in the config yet
+    if !ADDING.lock().map_err(|e| e.to_string())?.is_empty() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let ours = entry.file_name().to_string_lossy().starts_with("repo-");
+        if ours && path.is_dir() && !in_use.contains(&canonical(&path)) && std::fs::remove_dir_all(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
 
 pub fn validate_repo_path(path: &Path) -> Result<u32, String> {
     let marketplace_json = path.join(".claude-plugin").join("marketplace.json");
@@ -124,3 +665,96 @@ pub async fn validate_marketplace_repository(path: String) -> Result<ValidationR
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin(name: &str, description: &str, skills: &[&str]) -> Plugin {
+        Plugin {
+            name: name.to_string(),
+            description: description.to_string(),
+            category: None,
+            author: None,
+            authors: None,
+            source_repo: None,
+            source_url: None,
+            source_path: None,
+            skills: skills.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_repo_key_matches_equivalent_urls() {
+        let key = repo_key("https://github.com/Org/Repo");
+        assert_eq!(key, "github.com/org/repo");
+        assert_eq!(repo_key("https://github.com/org/repo.git/"), key);
+        assert_eq!(repo_key("git@github.com:org/repo.git"), key);
+        assert_eq!(repo_key("ssh://git@github.com/org/repo"), key);
+        assert_ne!(repo_key("https://gitlab.com/org/repo"), key);
+    }
+
+    #[test]
+    fn test_only_conflicts_count_as_local_changes() {
+        let conflict = git2::Error::new(ErrorCode::Conflict, ErrorClass::Checkout, "1 conflict prevents checkout");
+        assert!(matches!(GitError::from(conflict), GitError::LocalChanges));
+        let locked = git2::Error::new(ErrorCode::GenericError, ErrorClass::Checkout, "failed to unlink file");
+        assert!(matches!(GitError::from(locked), GitError::Git(_)));
+    }
+
+    #[test]
+    fn test_plugin_changes() {
+        let before = vec![plugin("docs", "Docs", &["./skills/pdf"]), plugin("old", "", &[]), plugin("dev", "Dev", &["./skills/git"])];
+        let after = vec![plugin("docs", "Docs", &["./skills/pdf"]), plugin("new", "", &[]), plugin("dev", "Dev tools", &["./skills/git"])];
+        let files = vec!["skills/pdf/SKILL.md".to_string(), "skills/pdf-extra/SKILL.md".to_string()];
+
+        let (added, removed, changed) = plugin_changes(&before, &after, &files);
+        assert_eq!(added, vec!["new"]);
+        assert_eq!(removed, vec!["old"]);
+        assert_eq!(changed, vec!["docs", "dev"]);
+    }
+
+    fn commit_file(repo: &Repository, file: &str, content: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        std::fs::write(workdir.join(file), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, &format!("update {}", file), &tree, &parents).unwrap()
+    }
+
+    #[test]
+    fn test_clone_and_fast_forward() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let base = tmp.path();
+        let origin = Repository::init(base.join("origin")).unwrap();
+        commit_file(&origin, "README.md", "one\n");
+
+        let clone = base.join("clone");
+        let url = base.join("origin").to_string_lossy().to_string();
+        clone_repository(&url, &clone, None, &mut |_| true).unwrap();
+        let status = repository_status(&clone).unwrap();
+        assert!(status.is_git && !status.dirty);
+        assert_eq!((status.ahead, status.behind), (0, 0));
+
+        let new = commit_file(&origin, "SKILL.md", "two\n");
+        let ff = fast_forward(&clone, None, &mut |_| true).unwrap();
+        assert_eq!(ff.new, new);
+        assert_eq!(ff.commits.len(), 1);
+        assert!(ff.commits[0].ends_with("update SKILL.md"));
+        assert_eq!(ff.changed_files, vec!["SKILL.md"]);
+        assert_eq!(std::fs::read_to_string(clone.join("SKILL.md")).unwrap(), "two\n");
+
+        // local edits block the update instead of being overwritten
+        commit_file(&origin, "SKILL.md", "three\n");
+        std::fs::write(clone.join("SKILL.md"), "local\n").unwrap();
+        assert!(repository_status(&clone).unwrap().dirty);
+        assert!(matches!(fast_forward(&clone, None, &mut |_| true), Err(GitError::LocalChanges)));
+
+        assert!(!repository_status(base).unwrap().is_git);
+    }
+}
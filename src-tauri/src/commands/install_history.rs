@@ -33,6 +33,21 @@ pub fn get_install_history(
     Ok(records)
 }
 
+/// 历史记录中成功安装过的项目路径（去重）
+pub fn installed_project_paths() -> Vec<String> {
+    if let Ok(history) = get_install_history(None, None) {
+        history.into_iter()
+            .filter(|r| r.status == "success")
+            .filter_map(|r| r.project_path)
+            .filter(|p| !p.is_empty())
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect()
+    } else {
+        Vec::new()
+    }
+}
+
 /// 记录安装历史
 pub fn record_install_history(
     plugin_name: &str,
@@ -1,15 +1,24 @@
 use crate::commands::agent_config;
+use crate::commands::compatibility;
+use crate::commands::format_adapters;
 use crate::commands::install_history;
+use crate::commands::projects_registry;
+use crate::commands::skill_watcher;
+use crate::commands::symlinks;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 #[tauri::command]
 pub fn install_local_skill(
+    app: tauri::AppHandle,
     skill_path: String,
     scope: String, // "global" or "project"
     selected_agents: Vec<String>,
     scope_path: Option<String>,
+    native_format: Option<bool>, // convert into the agent's own rule file when it has a rule_format
+    link_policy: Option<String>, // how symlinks inside the skill are copied: "follow" (default) | "preserve" | "skip"
 ) -> Result<String, String> {
+    let link_policy = symlinks::LinkPolicy::parse(link_policy.as_deref(), symlinks::LinkPolicy::Follow)?;
     let source_path = PathBuf::from(&skill_path);
 
     // 1. Validate source
@@ -26,10 +35,49 @@ pub fn install_local_skill(
         .ok_or("Invalid skill path name")?
         .to_string();
 
+    // Registered projects supply default agents when none were picked
+    let selected_agents = projects_registry::resolve_install_agents(&scope, scope_path.as_deref(), &selected_agents);
+    if selected_agents.is_empty() {
+        return Err("No agents selected".to_string());
+    }
+
     let mut results = Vec::new();
+    let agent_configs = agent_config::all_agents();
 
     // 2. Install for each agent
     for agent_id in &selected_agents {
+        let rules = if native_format.unwrap_or(false) {
+            agent_config::get_agent_rules_path(agent_id, &scope, scope_path.as_deref())
+        } else {
+            None
+        };
+
+        // Skills the agent can't load (over its size limit) are refused, not copied
+        if let Some(agent) = agent_configs.iter().find(|a| &a.id == agent_id) {
+            let issues = compatibility::check_skill(agent, &source_path, rules.is_some());
+            if compatibility::has_blocking_issue(&issues) {
+                let reason: Vec<_> = issues.iter().filter(|i| i.severity == "error").map(|i| i.message.clone()).collect();
+                results.push(format!("{}: Incompatible ({})", agent_id, reason.join("; ")));
+                continue;
+            }
+        }
+
+        if let Some((format, rules_dir)) = rules {
+            match format_adapters::install_native_rule(
+                &format,
+                &source_path,
+                &rules_dir,
+                agent_id,
+                &skill_name,
+                &scope,
+                scope_path.clone(),
+            ) {
+                Ok(_) => results.push(format!("{}: Success", agent_id)),
+                Err(e) => results.push(format!("{}: Convert failed ({})", agent_id, e)),
+            }
+            continue;
+        }
+
         // Resolve Target Path
         let target_root = if scope == "global" {
             agent_config::get_agent_global_path(&agent_id)
@@ -54,7 +102,7 @@ pub fn install_local_skill(
             let target_path = root.join(&skill_name);
 
             // Copy
-            if let Err(e) = copy_dir_all(&source_path, &target_path) {
+            if let Err(e) = copy_dir_all(&source_path, &target_path, link_policy) {
                  results.push(format!(
                     "{}: Copy failed ({})",
                     agent_id,
@@ -80,6 +128,14 @@ pub fn install_local_skill(
         None
     };
 
+    if success && scope == "project" {
+        if let Some(ref p) = scope_path {
+            if let Err(e) = projects_registry::ensure_registered(p) {
+                eprintln!("Failed to register project: {}", e);
+            }
+        }
+    }
+
     if let Err(e) = install_history::record_install_history(
         &skill_name,
         &selected_agents,
@@ -92,22 +148,14 @@ pub fn install_local_skill(
         eprintln!("Failed to record install history: {}", e);
     }
 
+    // The install may have created an agent's skills folder for the first time
+    skill_watcher::refresh(&app);
     Ok(results.join(", "))
 }
 
-fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
-    fs::create_dir_all(&dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        // Skip .git or other ignored files if needed, but for now copy all
-        if ty.is_dir() {
-            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        } else {
-            fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        }
-    }
-    Ok(())
+/// Installed skills should be self-contained, so by default links are copied as the files they point to
+fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>, policy: symlinks::LinkPolicy) -> std::io::Result<()> {
+    symlinks::copy_dir(src.as_ref(), dst.as_ref(), policy)
 }
 
 fn create_install_metadata(target_dir: &Path, source_path: &str, scope: &str) -> std::io::Result<()> {
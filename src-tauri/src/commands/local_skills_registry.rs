@@ -1,5 +1,6 @@
 use std::fs;
 use std::path::PathBuf;
+use crate::commands::skill_watcher;
 use crate::types::{LocalSkillsRegistry, LocalDirectory};
 use dirs;
 
@@ -69,8 +70,15 @@ fn save_registry(registry: &LocalSkillsRegistry) -> Result<(), String> {
     fs::write(path, content).map_err(|e| format!("Failed to write registry: {}", e))
 }
 
+/// Paths of all registered local skills directories
+pub fn registered_directory_paths() -> Vec<String> {
+    load_registry()
+        .map(|r| r.registered_directories.into_iter().map(|d| d.path).collect())
+        .unwrap_or_default()
+}
+
 #[tauri::command]
-pub async fn register_local_directory(path: String, name: Option<String>) -> Result<bool, String> {
+pub async fn register_local_directory(app: tauri::AppHandle, path: String, name: Option<String>) -> Result<bool, String> {
     let mut registry = load_registry()?;
     
     // Normalize path just in case, though usually frontend sends absolute path
@@ -96,6 +104,7 @@ pub async fn register_local_directory(path: String, name: Option<String>) -> Res
         registry.registered_directories.push(LocalDirectory { path, name });
         registry.last_updated = chrono::Utc::now().to_rfc3339();
         save_registry(&registry)?;
+        skill_watcher::refresh(&app);
         Ok(true)
     } else {
         Ok(false) // Already registered
@@ -103,13 +112,14 @@ pub async fn register_local_directory(path: String, name: Option<String>) -> Res
 }
 
 #[tauri::command]
-pub async fn unregister_local_directory(path: String) -> Result<bool, String> {
+pub async fn unregister_local_directory(app: tauri::AppHandle, path: String) -> Result<bool, String> {
     let mut registry = load_registry()?;
     
     if let Some(index) = registry.registered_directories.iter().position(|r| r.path == path) {
         registry.registered_directories.remove(index);
         registry.last_updated = chrono::Utc::now().to_rfc3339();
         save_registry(&registry)?;
+        skill_watcher::refresh(&app);
         Ok(true)
     } else {
         Ok(false) // Not found
@@ -1,6 +1,6 @@
 use std::fs;
-use std::path::Path;
-use regex::Regex;
+use std::path::{Path, PathBuf};
+use crate::commands::{frontmatter, symlinks};
 use crate::types::{LocalSkill, SkillMetadata, LocalSkillScanResult, SkillSource};
 
 /// 扫描指定目录下的本地 skills (递归)
@@ -19,11 +19,7 @@ pub async fn scan_local_skills(directory: String) -> Result<LocalSkillScanResult
         });
     }
 
-    let mut skills = Vec::new();
-    let mut errors = Vec::new();
-
-    // 开始递归扫描，最大深度 5
-    scan_dir_recursive(path, &mut skills, &mut errors, 0, 5);
+    let (skills, errors) = collect_local_skills(path);
 
     let total = skills.len();
     Ok(LocalSkillScanResult {
@@ -35,10 +31,48 @@ pub async fn scan_local_skills(directory: String) -> Result<LocalSkillScanResult
     })
 }
 
+/// All skills below a local directory, plus the ones that failed to parse
+pub fn collect_local_skills(path: &Path) -> (Vec<LocalSkill>, Vec<String>) {
+    let mut skills = Vec::new();
+    let mut errors = Vec::new();
+
+    for dir in find_skill_dirs(path) {
+        match parse_skill_metadata(&dir.join("SKILL.md")) {
+            Ok(metadata) => {
+                skills.push(LocalSkill {
+                    name: metadata.name,
+                    description: metadata.description,
+                    path: dir.to_string_lossy().to_string(),
+                    source: SkillSource::LocalDirectory,
+                    has_scripts: dir.join("scripts").exists(),
+                    has_references: dir.join("references").exists(),
+                    has_assets: dir.join("assets").exists(),
+                    size_bytes: calculate_dir_size(&dir),
+                    link_target: symlinks::linked_target(path, &dir).map(|t| t.to_string_lossy().to_string()),
+                });
+            }
+            Err(e) => {
+                errors.push(format!("Failed to parse {}: {}", dir.display(), e));
+            }
+        }
+    }
+    (skills, errors)
+}
+
+/// Folders below a local directory that hold a SKILL.md
+pub fn find_skill_dirs(path: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    // 开始递归扫描，最大深度 5；符号链接会被跟随，指回上层目录的循环链接会被跳过
+    let mut guard = symlinks::LoopGuard::new();
+    guard.enter(path);
+    scan_dir_recursive(path, &mut guard, &mut dirs, 0, 5);
+    dirs
+}
+
 fn scan_dir_recursive(
     dir: &Path,
-    skills: &mut Vec<LocalSkill>,
-    errors: &mut Vec<String>,
+    guard: &mut symlinks::LoopGuard,
+    dirs: &mut Vec<PathBuf>,
     depth: u32,
     max_depth: u32
 ) {
@@ -53,30 +87,8 @@ fn scan_dir_recursive(
     ];
 
     // 1. 检查当前目录是否是 Skill (存在 SKILL.md)
-    let skill_md_path = dir.join("SKILL.md");
-    if skill_md_path.exists() {
-        match parse_skill_metadata(&skill_md_path) {
-            Ok(metadata) => {
-                let size = calculate_dir_size(dir);
-                let has_scripts = dir.join("scripts").exists();
-                let has_references = dir.join("references").exists();
-                let has_assets = dir.join("assets").exists();
-
-                skills.push(LocalSkill {
-                    name: metadata.name,
-                    description: metadata.description,
-                    path: dir.to_string_lossy().to_string(),
-                    source: SkillSource::LocalDirectory,
-                    has_scripts,
-                    has_references,
-                    has_assets,
-                    size_bytes: size,
-                });
-            }
-            Err(e) => {
-                errors.push(format!("Failed to parse {}: {}", dir.display(), e));
-            }
-        }
+    if dir.join("SKILL.md").exists() {
+        dirs.push(dir.to_path_buf());
     }
 
     // 2. 遍历子目录
@@ -94,7 +106,8 @@ fn scan_dir_recursive(
                     }
                 }
                 
-                scan_dir_recursive(&path, skills, errors, depth + 1, max_depth);
+                // A link back to a folder above us is a loop and is not followed
+                guard.descend(&path, |g| scan_dir_recursive(&path, g, dirs, depth + 1, max_depth));
             }
         }
     }
@@ -102,64 +115,21 @@ fn scan_dir_recursive(
 
 /// 从 SKILL.md 解析元数据
 fn parse_skill_metadata(file_path: &Path) -> Result<SkillMetadata, String> {
-    let content = fs::read_to_string(file_path)
-        .map_err(|e| format!("读取文件失败: {}", e))?;
-
-    // 提取 YAML frontmatter (---\n...metadata...\n---)
-    let frontmatter_pattern = Regex::new(r"(?m)^---\s*\r?\n([\s\S]*?)\r?\n---")
-        .map_err(|e| format!("Regex错误: {}", e))?;
-
-    if let Some(caps) = frontmatter_pattern.captures(&content) {
-        let yaml_str = &caps[1];
-
-        // 简单的 YAML 解析（对 name 和 description 字段）
-        let mut name = String::new();
-        let mut description = String::new();
-
-        for line in yaml_str.lines() {
-            let line = line.trim();
-            if line.starts_with("name:") {
-                name = line.strip_prefix("name:")
-                    .unwrap_or("")
-                    .trim()
-                    .trim_matches('"')
-                    .to_string();
-            }
-            if line.starts_with("description:") {
-                description = line.strip_prefix("description:")
-                    .unwrap_or("")
-                    .trim()
-                    .trim_matches('"')
-                    .to_string();
-            }
-        }
-
-        if name.is_empty() || description.is_empty() {
-            return Err("缺少 name 或 description 字段".to_string());
-        }
+    let parsed = frontmatter::parse_file(file_path).map_err(|e| e.to_string())?;
+    if parsed.frontmatter.is_none() {
+        return Err("未找到YAML frontmatter".to_string());
+    }
 
-        Ok(SkillMetadata { name, description })
-    } else {
-        Err("未找到YAML frontmatter".to_string())
+    let name = parsed.name().unwrap_or_default().trim().to_string();
+    let description = parsed.description().unwrap_or_default().trim().to_string();
+    if name.is_empty() || description.is_empty() {
+        return Err("缺少 name 或 description 字段".to_string());
     }
+
+    Ok(SkillMetadata { name, description })
 }
 
-/// 计算目录大小（递归）
+/// 计算目录大小（递归，跟随符号链接，与安装时复制的内容一致）
 fn calculate_dir_size(path: &Path) -> u64 {
-    let mut total = 0;
-
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_dir() {
-                    total += calculate_dir_size(&path);
-                } else if let Ok(metadata) = fs::metadata(&path) {
-                    total += metadata.len();
-                }
-            }
-        }
-    }
-
-    total
+    symlinks::dir_size(path, symlinks::LinkPolicy::Follow).unwrap_or(0)
 }
@@ -1,6 +1,8 @@
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tauri::Manager;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -82,3 +84,32 @@ pub fn get_marketplace_data(app: tauri::AppHandle) -> Result<MarketplaceData, St
 
     Ok(data)
 }
+
+/// Last aggregated marketplace together with the signature it was built from
+static MARKETPLACE_CACHE: Lazy<Mutex<Option<(String, MarketplaceData)>>> = Lazy::new(|| Mutex::new(None));
+
+/// Same as `get_marketplace_data`, but reuses the previous result while the repository
+/// list and every enabled repository's marketplace.json are unchanged.
+pub fn get_marketplace_data_cached(app: &tauri::AppHandle) -> Result<MarketplaceData, String> {
+    let config = crate::commands::config_manager::load_config(app)?;
+    let mut signature = serde_json::to_string(&config.marketplace.repositories).map_err(|e| e.to_string())?;
+    for repo in config.marketplace.repositories.iter().filter(|r| r.enabled) {
+        let path = Path::new(&repo.local_path).join(".claude-plugin").join("marketplace.json");
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        signature.push_str(&format!("|{:?}", modified));
+    }
+
+    if let Ok(cache) = MARKETPLACE_CACHE.lock() {
+        if let Some((cached_sig, data)) = cache.as_ref() {
+            if *cached_sig == signature {
+                return Ok(data.clone());
+            }
+        }
+    }
+
+    let data = get_marketplace_data(app.clone())?;
+    if let Ok(mut cache) = MARKETPLACE_CACHE.lock() {
+        *cache = Some((signature, data.clone()));
+    }
+    Ok(data)
+}
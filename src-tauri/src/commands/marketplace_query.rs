@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::commands::marketplace::{self, Plugin};
+use crate::commands::{plugin_installer, plugin_scanner};
+
+const DEFAULT_PAGE_SIZE: usize = 24;
+
+// 市场查询条件
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MarketplaceQuery {
+    pub category: Option<String>,
+    pub author: Option<String>,
+    pub source_repo: Option<String>,
+    pub has_scripts: Option<bool>,
+    pub installed: Option<bool>,
+    pub installed_in_agent: Option<String>,
+    pub sort_by: Option<String>, // "name" (default) | "category" | "skills" | "installed"
+    #[serde(default)]
+    pub sort_desc: bool,
+    pub page: Option<usize>, // 1-based
+    pub page_size: Option<usize>,
+}
+
+// 带安装状态的市场插件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarketplaceEntry {
+    #[serde(flatten)]
+    pub plugin: Plugin,
+    pub has_scripts: bool,
+    pub installed: bool,
+    pub installed_agents: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+// 分面统计
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MarketplaceFacets {
+    pub categories: Vec<FacetCount>,
+    pub repos: Vec<FacetCount>,
+    pub authors: Vec<FacetCount>,
+}
+
+// 查询结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarketplaceQueryResult {
+    pub plugins: Vec<MarketplaceEntry>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub total_pages: usize,
+    pub facets: MarketplaceFacets,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Facet {
+    Category,
+    Repo,
+    Author,
+}
+
+fn authors_of(plugin: &Plugin) -> Vec<String> {
+    let mut names: Vec<String> = plugin
+        .author
+        .iter()
+        .chain(plugin.authors.iter().flatten())
+        .map(|a| a.name.clone())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Does `entry` pass every filter except the one for `ignore` (used when counting that facet)
+fn matches(entry: &MarketplaceEntry, q: &MarketplaceQuery, ignore: Option<Facet>) -> bool {
+    let plugin = &entry.plugin;
+    if ignore != Some(Facet::Category) {
+        if let Some(ref category) = q.category {
+            if plugin.category.as_ref() != Some(category) {
+                return false;
+            }
+        }
+    }
+    if ignore != Some(Facet::Repo) {
+        if let Some(ref repo) = q.source_repo {
+            if plugin.source_repo.as_ref() != Some(repo) {
+                return false;
+            }
+        }
+    }
+    if ignore != Some(Facet::Author) {
+        if let Some(ref author) = q.author {
+            if !authors_of(plugin).contains(author) {
+                return false;
+            }
+        }
+    }
+    if q.has_scripts.is_some_and(|v| v != entry.has_scripts) {
+        return false;
+    }
+    if q.installed.is_some_and(|v| v != entry.installed) {
+        return false;
+    }
+    if let Some(ref agent) = q.installed_in_agent {
+        if !entry.installed_agents.contains(agent) {
+            return false;
+        }
+    }
+    true
+}
+
+fn count_facet(entries: &[MarketplaceEntry], q: &MarketplaceQuery, facet: Facet) -> Vec<FacetCount> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in entries.iter().filter(|e| matches(e, q, Some(facet))) {
+        let values = match facet {
+            Facet::Category => entry.plugin.category.iter().cloned().collect(),
+            Facet::Repo => entry.plugin.source_repo.iter().cloned().collect(),
+            Facet::Author => authors_of(&entry.plugin),
+        };
+        for value in values {
+            *counts.entry(value).or_insert(0) += 1;
+        }
+    }
+    let mut out: Vec<FacetCount> = counts.into_iter().map(|(value, count)| FacetCount { value, count }).collect();
+    out.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    out
+}
+
+/// Filter, facet, sort and paginate
+fn run_query(entries: Vec<MarketplaceEntry>, q: &MarketplaceQuery) -> MarketplaceQueryResult {
+    let facets = MarketplaceFacets {
+        categories: count_facet(&entries, q, Facet::Category),
+        repos: count_facet(&entries, q, Facet::Repo),
+        authors: count_facet(&entries, q, Facet::Author),
+    };
+
+    let mut filtered: Vec<MarketplaceEntry> = entries.into_iter().filter(|e| matches(e, q, None)).collect();
+    filtered.sort_by(|a, b| {
+        let by_name = a.plugin.name.to_lowercase().cmp(&b.plugin.name.to_lowercase());
+        let key = match q.sort_by.as_deref() {
+            Some("category") => a.plugin.category.cmp(&b.plugin.category),
+            Some("skills") => a.plugin.skills.len().cmp(&b.plugin.skills.len()),
+            Some("installed") => a.installed.cmp(&b.installed),
+            _ => by_name,
+        };
+        // Descending flips the sort key only; ties stay in ascending name order
+        if q.sort_desc { key.reverse() } else { key }.then(by_name)
+    });
+
+    let total = filtered.len();
+    let page_size = q.page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+    let total_pages = total.div_ceil(page_size);
+    let page = q.page.unwrap_or(1).max(1);
+    let plugins = filtered.into_iter().skip((page - 1) * page_size).take(page_size).collect();
+
+    MarketplaceQueryResult { plugins, total, page, page_size, total_pages, facets }
+}
+
+/// 分面查询市场插件：过滤、排序、分页，并返回分类/仓库/作者计数
+#[tauri::command]
+pub fn query_marketplace(app: tauri::AppHandle, query: MarketplaceQuery) -> Result<MarketplaceQueryResult, String> {
+    let data = marketplace::get_marketplace_data_cached(&app)?;
+
+    // Which agents have each plugin, from the per-skill scan view (standalone skills don't belong to a plugin)
+    let mut installed: HashMap<String, Vec<String>> = HashMap::new();
+    let scan = plugin_scanner::cached_full_scan(&app)?;
+    for skill in scan.skills.into_iter().filter(|s| s.grouped_by != "standalone") {
+        let agents = installed.entry(skill.plugin_name).or_default();
+        if !agents.contains(&skill.agent_id) {
+            agents.push(skill.agent_id);
+        }
+    }
+
+    let mut roots = plugin_installer::SourceRoots::new(&app);
+    let entries = data
+        .plugins
+        .into_iter()
+        .map(|plugin| {
+            let has_scripts = roots
+                .get(&plugin)
+                .map(|root| {
+                    plugin
+                        .skills
+                        .iter()
+                        .any(|s| root.join(s.trim_start_matches("./")).join("scripts").is_dir())
+                })
+                .unwrap_or(false);
+            let installed_agents = installed.get(&plugin.name).cloned().unwrap_or_default();
+            MarketplaceEntry {
+                installed: !installed_agents.is_empty(),
+                installed_agents,
+                has_scripts,
+                plugin,
+            }
+        })
+        .collect();
+
+    Ok(run_query(entries, &query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, category: &str, repo: &str, installed: bool) -> MarketplaceEntry {
+        MarketplaceEntry {
+            plugin: Plugin {
+                name: name.to_string(),
+                description: String::new(),
+                category: Some(category.to_string()),
+                author: None,
+                authors: None,
+                source_repo: Some(repo.to_string()),
+                source_url: None,
+                source_path: None,
+                skills: vec![],
+            },
+            has_scripts: false,
+            installed,
+            installed_agents: if installed { vec!["claude".to_string()] } else { vec![] },
+        }
+    }
+
+    #[test]
+    fn test_facets_ignore_their_own_filter() {
+        let entries = vec![
+            entry("a", "docs", "official", true),
+            entry("b", "docs", "community", false),
+            entry("c", "dev", "official", false),
+        ];
+        let q = MarketplaceQuery {
+            category: Some("docs".to_string()),
+            page_size: Some(1),
+            ..Default::default()
+        };
+        let result = run_query(entries, &q);
+
+        assert_eq!(result.total, 2);
+        assert_eq!(result.total_pages, 2);
+        assert_eq!(result.plugins[0].plugin.name, "a");
+        // category counts still show "dev", repo counts are restricted to "docs"
+        assert_eq!(result.facets.categories.len(), 2);
+        assert_eq!(result.facets.repos.iter().map(|f| f.count).sum::<usize>(), 2);
+    }
+
+    #[test]
+    fn test_descending_sort_keeps_name_order_for_ties() {
+        let entries = vec![
+            entry("b", "docs", "official", false),
+            entry("a", "docs", "official", false),
+            entry("c", "dev", "official", true),
+        ];
+        let q = MarketplaceQuery {
+            sort_by: Some("installed".to_string()),
+            sort_desc: true,
+            ..Default::default()
+        };
+        let names: Vec<String> = run_query(entries, &q).plugins.into_iter().map(|e| e.plugin.name).collect();
+        assert_eq!(names, ["c", "a", "b"]);
+    }
+}
@@ -1,5 +1,17 @@
 pub mod agent_config;
+pub mod agent_detector;
+pub mod compatibility;
+pub mod skill_health;
+pub mod skill_editor;
+pub mod skill_lint;
+pub mod skill_templates;
+pub mod skill_conflicts;
+pub mod skill_diff;
+pub mod skill_search;
+pub mod format_adapters;
+pub mod frontmatter;
 pub mod marketplace;
+pub mod marketplace_query;
 pub mod plugin_installer;
 pub mod plugin_scanner;
 pub mod skill_metadata;
@@ -10,8 +22,14 @@ pub mod system;
 pub mod git_manager;
 pub mod config_manager;
 pub mod marketplace_manager;
+pub mod refresh_scheduler;
 pub mod local_skills_scanner;
 pub mod local_skills_registry;
+pub mod projects_registry;
 pub mod local_skills_installer;
 pub mod translator;
-
+pub mod scan_index;
+pub mod symlinks;
+pub mod skill_watcher;
+pub mod project_discovery;
+pub mod credential_store;
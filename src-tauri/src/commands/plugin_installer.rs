@@ -1,5 +1,11 @@
 use crate::commands::agent_config;
+use crate::commands::compatibility;
+use crate::commands::format_adapters;
 use crate::commands::marketplace;
+use crate::commands::projects_registry;
+use crate::commands::skill_watcher;
+use crate::commands::symlinks;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::Manager;
@@ -11,7 +17,10 @@ pub fn install_plugin(
     agents: Vec<String>,
     scope_type: String, // "global" or "project"
     scope_path: Option<String>,
+    native_format: Option<bool>, // convert skills into the agent's own rule files when it has a rule_format
+    link_policy: Option<String>, // how symlinks inside skills are copied: "follow" (default) | "preserve" | "skip"
 ) -> Result<String, String> {
+    let link_policy = symlinks::LinkPolicy::parse(link_policy.as_deref(), symlinks::LinkPolicy::Follow)?;
     // 1. Get marketplace data to find skills
     let data = marketplace::get_marketplace_data(app.clone()).map_err(|e| e)?;
     let plugin = data
@@ -21,38 +30,64 @@ pub fn install_plugin(
         .ok_or_else(|| format!("Plugin {} not found", plugin_name))?;
 
     // 2. Resolve Source Root (Skill-Box root)
-    let mut paths = Vec::new();
+    let source_root = resolve_source_root(&app, plugin)?;
 
-    // If plugin has a pre-defined source path (e.g. from a configured repository), use it first
-    if let Some(ref sp) = plugin.source_path {
-        paths.push(PathBuf::from(sp));
+    // Registered projects supply default agents when none were picked
+    let agents = projects_registry::resolve_install_agents(&scope_type, scope_path.as_deref(), &agents);
+    if agents.is_empty() {
+        return Err("No agents selected".to_string());
     }
 
-    // Fallback paths for development or packaged resources
-    paths.push(PathBuf::from("Skill-Box"));
-    paths.push(PathBuf::from("../Skill-Box"));
-
-    // Check for packaged resources
-    if let Ok(resource_dir) = app.path().resource_dir() {
-        paths.push(resource_dir.join("Skill-Box"));
-        paths.push(resource_dir.join("_up_").join("Skill-Box"));
-    }
-    
-    let source_root = paths
-        .iter()
-        .find(|p| p.join(".claude-plugin/marketplace.json").exists())
-        .ok_or_else(|| {
-            let current_dir = std::env::current_dir().unwrap_or_default();
-            format!(
-                "Skill-Box repo not found. Searched in: {:?}. Current Dir: {:?}",
-                paths, current_dir
-            )
-        })?;
-
     let mut results = Vec::new();
+    let agent_configs = agent_config::all_agents();
 
     // 3. For each agent
     for agent_id in &agents {
+        // Skills the agent can't load (over its size limit) are refused, not copied
+        let incompatible = |source_path: &Path, native: bool| {
+            let agent = agent_configs.iter().find(|a| &a.id == agent_id)?;
+            let issues = compatibility::check_skill(agent, source_path, native);
+            compatibility::has_blocking_issue(&issues).then(|| {
+                issues
+                    .iter()
+                    .filter(|i| i.severity == "error")
+                    .map(|i| i.message.clone())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            })
+        };
+
+        // Agents with a native rule format get converted rule files instead of skill folders
+        if native_format.unwrap_or(false) {
+            if let Some((format, rules_dir)) =
+                agent_config::get_agent_rules_path(agent_id, &scope_type, scope_path.as_deref())
+            {
+                let before = results.len();
+                for skill_rel_path in &plugin.skills {
+                    let source_path = source_root.join(skill_rel_path.trim_start_matches("./"));
+                    if let Some(reason) = incompatible(&source_path, true) {
+                        results.push(format!("{}: Incompatible {:?} ({})", agent_id, source_path, reason));
+                        continue;
+                    }
+                    if let Err(e) = format_adapters::install_native_rule(
+                        &format,
+                        &source_path,
+                        &rules_dir,
+                        agent_id,
+                        &plugin_name,
+                        &scope_type,
+                        scope_path.clone(),
+                    ) {
+                        results.push(format!("{}: Convert failed for {:?} ({})", agent_id, source_path, e));
+                    }
+                }
+                if results.len() == before {
+                    results.push(format!("{}: Success", agent_id));
+                }
+                continue;
+            }
+        }
+
         // Resolve Target Path
         let target_root = if scope_type == "global" {
             agent_config::get_agent_global_path(&agent_id)
@@ -84,14 +119,26 @@ pub fn install_plugin(
                 let target_path = root.join(skill_name);
 
                 if source_path.exists() {
+                    if let Some(reason) = incompatible(&source_path, false) {
+                        results.push(format!(
+                            "{}: Incompatible {} ({})",
+                            agent_id,
+                            skill_name.to_string_lossy(),
+                            reason
+                        ));
+                        continue;
+                    }
                     // Start copy
-                    if let Err(e) = copy_dir_all(&source_path, &target_path) {
+                    if let Err(e) = copy_dir_all(&source_path, &target_path, link_policy) {
                         results.push(format!(
                             "{}: Copy failed for {} ({})",
                             agent_id,
                             skill_name.to_string_lossy(),
                             e
                         ));
+                    } else {
+                        // Provenance lets the scanner group the skills back into this plugin
+                        let _ = create_install_metadata(&target_path, plugin, &scope_type);
                     }
                 } else {
                     results.push(format!(
@@ -114,6 +161,14 @@ pub fn install_plugin(
         None
     };
 
+    if success && scope_type == "project" {
+        if let Some(ref p) = scope_path {
+            if let Err(e) = projects_registry::ensure_registered(p) {
+                eprintln!("Failed to register project: {}", e);
+            }
+        }
+    }
+
     if let Err(e) = super::install_history::record_install_history(
         &plugin_name,
         &agents,
@@ -126,19 +181,79 @@ pub fn install_plugin(
         eprintln!("Failed to record install history: {}", e);
     }
 
+    // The install may have created an agent's skills folder for the first time
+    skill_watcher::refresh(&app);
     Ok(results.join(", "))
 }
 
-fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> std::io::Result<()> {
-    fs::create_dir_all(&dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        } else {
-            fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        }
+/// Locate the repository root that holds the plugin's skills (Skill-Box root by default)
+pub fn resolve_source_root(app: &tauri::AppHandle, plugin: &marketplace::Plugin) -> Result<PathBuf, String> {
+    let mut paths = Vec::new();
+
+    // If plugin has a pre-defined source path (e.g. from a configured repository), use it first
+    if let Some(ref sp) = plugin.source_path {
+        paths.push(PathBuf::from(sp));
+    }
+
+    // Fallback paths for development or packaged resources
+    paths.push(PathBuf::from("Skill-Box"));
+    paths.push(PathBuf::from("../Skill-Box"));
+
+    // Check for packaged resources
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        paths.push(resource_dir.join("Skill-Box"));
+        paths.push(resource_dir.join("_up_").join("Skill-Box"));
     }
+    
+    paths
+        .iter()
+        .find(|p| p.join(".claude-plugin/marketplace.json").exists())
+        .cloned()
+        .ok_or_else(|| {
+            let current_dir = std::env::current_dir().unwrap_or_default();
+            format!(
+                "Skill-Box repo not found. Searched in: {:?}. Current Dir: {:?}",
+                paths, current_dir
+            )
+        })
+}
+
+/// `resolve_source_root` remembered per source path, so plugins from the same repository
+/// share one lookup and plugins that are never looked at cost nothing
+pub struct SourceRoots<'a> {
+    app: &'a tauri::AppHandle,
+    resolved: HashMap<Option<String>, Option<PathBuf>>,
+}
+
+impl<'a> SourceRoots<'a> {
+    pub fn new(app: &'a tauri::AppHandle) -> Self {
+        SourceRoots { app, resolved: HashMap::new() }
+    }
+
+    pub fn get(&mut self, plugin: &marketplace::Plugin) -> Option<PathBuf> {
+        let app = self.app;
+        self.resolved
+            .entry(plugin.source_path.clone())
+            .or_insert_with(|| resolve_source_root(app, plugin).ok())
+            .clone()
+    }
+}
+
+fn create_install_metadata(target_dir: &Path, plugin: &marketplace::Plugin, scope: &str) -> std::io::Result<()> {
+    use serde_json::json;
+    let metadata_path = target_dir.join(".metadata.json");
+    let metadata = json!({
+        "plugin_name": plugin.name,
+        "source_repo": plugin.source_repo,
+        "installation_scope": scope,
+        "installed_date": chrono::Utc::now().to_rfc3339(),
+        "source_type": "Marketplace"
+    });
+    fs::write(metadata_path, serde_json::to_string_pretty(&metadata)?)?;
     Ok(())
 }
+
+/// Installed skills should be self-contained, so by default links are copied as the files they point to
+fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>, policy: symlinks::LinkPolicy) -> std::io::Result<()> {
+    symlinks::copy_dir(src.as_ref(), dst.as_ref(), policy)
+}
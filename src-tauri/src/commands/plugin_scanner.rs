@@ -1,16 +1,24 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use crate::types::{InstalledPlugin, PluginLocation, ScanResult, ScanSummary};
-use crate::commands::{agent_config, marketplace, install_history};
+use crate::types::{InstalledPlugin, InstalledSkill, PluginLocation, ScanResult, ScanSummary};
+use crate::commands::{agent_config, format_adapters, frontmatter, marketplace, project_discovery, scan_index, symlinks};
 
 #[tauri::command]
 pub fn scan_installed_plugins(
     app: tauri::AppHandle,
     scope: Option<String>,
     project_path: Option<String>,
+    refresh: Option<bool>, // ignore the scan index and re-read every skill
 ) -> Result<ScanResult, String> {
+    let started = std::time::Instant::now();
+    let force = refresh.unwrap_or(false);
+    let full = scope.is_none() && project_path.is_none();
     let mut result = ScanResult {
         total_count: 0,
         by_scope: ScanSummary {
@@ -19,11 +27,16 @@ pub fn scan_installed_plugins(
         },
         by_agent: HashMap::new(),
         plugins: Vec::new(),
+        skills: Vec::new(),
+        duration_ms: 0,
+        index_stats: scan_index::IndexStats::default(),
     };
 
     // 获取市场数据和所有代理配置
-    let marketplace = marketplace::get_marketplace_data(app)?;
+    let marketplace = marketplace::get_marketplace_data_cached(&app)?;
     let agents = agent_config::all_agents();
+    // Skills converted into native rule files (Cursor .mdc etc.) live outside the skill dirs
+    let native_rules = format_adapters::existing_native_rules();
 
     // 扫描全局作用域
     if scope.is_none() || scope.as_deref() == Some("global") {
@@ -36,11 +49,16 @@ pub fn scan_installed_plugins(
                         "global",
                         None,
                         &marketplace,
+                        force,
                         &mut result,
                     )?;
                 }
             }
         }
+
+        for entry in native_rules.iter().filter(|e| e.scope == "global") {
+            process_native_rule(entry, force, &mut result)?;
+        }
     }
 
     // 扫描项目作用域
@@ -48,20 +66,17 @@ pub fn scan_installed_plugins(
         let project_paths = if let Some(path) = project_path {
             vec![path]
         } else {
-            // 如果没有指定路径，尝试从历史记录中发现所有已安装过的项目路径
-            if let Ok(history) = install_history::get_install_history(None, None) {
-                history.into_iter()
-                    .filter(|r| r.status == "success")
-                    .filter_map(|r| r.project_path)
-                    .filter(|p| !p.is_empty())
-                    .collect::<std::collections::HashSet<_>>()
-                    .into_iter()
-                    .collect()
-            } else {
-                Vec::new()
-            }
+            // 如果没有指定路径，使用已注册项目和工作区扫描发现的项目
+            project_discovery::known_project_paths()
         };
 
+        for entry in native_rules.iter().filter(|e| {
+            e.scope == "project"
+                && e.project_path.as_ref().is_some_and(|p| project_paths.contains(p))
+        }) {
+            process_native_rule(entry, force, &mut result)?;
+        }
+
         for proj_path in project_paths {
             for agent in &agents {
                 if let Some(project_skill_path) =
@@ -74,6 +89,7 @@ pub fn scan_installed_plugins(
                             "project",
                             Some(proj_path.clone()),
                             &marketplace,
+                            force,
                             &mut result,
                         )?;
                     }
@@ -82,10 +98,46 @@ pub fn scan_installed_plugins(
         }
     }
 
+    if let Err(e) = scan_index::flush() {
+        eprintln!("Failed to save scan index: {}", e);
+    }
+
     result.total_count = result.plugins.len();
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    if full {
+        if let Ok(mut last) = LAST_FULL_SCAN.lock() {
+            *last = Some((Instant::now(), result.clone()));
+        }
+    }
     Ok(result)
 }
 
+/// How long a full scan is reused by search and marketplace queries when nothing invalidated it
+const FULL_SCAN_TTL: Duration = Duration::from_secs(30);
+
+/// Last full scan (all scopes, all known projects) and when it was taken
+static LAST_FULL_SCAN: Lazy<Mutex<Option<(Instant, ScanResult)>>> = Lazy::new(|| Mutex::new(None));
+
+/// A full scan, reusing the last one while it is fresh. The watcher and every install or
+/// registration drop it, the TTL covers changes made while the watcher is off.
+pub fn cached_full_scan(app: &tauri::AppHandle) -> Result<ScanResult, String> {
+    if let Ok(last) = LAST_FULL_SCAN.lock() {
+        if let Some((at, scan)) = last.as_ref() {
+            if at.elapsed() < FULL_SCAN_TTL {
+                return Ok(scan.clone());
+            }
+        }
+    }
+    scan_installed_plugins(app.clone(), None, None, None)
+}
+
+/// Forget the cached full scan so the next `cached_full_scan` reads the disk again
+pub fn invalidate_full_scan() {
+    if let Ok(mut last) = LAST_FULL_SCAN.lock() {
+        *last = None;
+    }
+}
+
 #[tauri::command]
 pub fn search_installed_plugins(
     app: tauri::AppHandle,
@@ -94,11 +146,11 @@ pub fn search_installed_plugins(
     project_path: Option<String>,
 ) -> Result<Vec<InstalledPlugin>, String> {
     if query.is_empty() {
-        let all = scan_installed_plugins(app, scope, project_path)?;
+        let all = scan_installed_plugins(app, scope, project_path, None)?;
         return Ok(all.plugins);
     }
 
-    let all_plugins_result = scan_installed_plugins(app, scope, project_path)?;
+    let all_plugins_result = scan_installed_plugins(app, scope, project_path, None)?;
     let mut results = Vec::new();
     let query_lower = query.to_lowercase();
 
@@ -139,6 +191,7 @@ fn scan_directory(
     scope: &str,
     project_path: Option<String>,
     marketplace: &marketplace::MarketplaceData,
+    force: bool,
     result: &mut ScanResult,
 ) -> Result<(), String> {
     // Recursively find all directories that contain SKILL.md
@@ -151,77 +204,86 @@ fn scan_directory(
             .unwrap_or("unknown")
             .to_string();
 
-        // ---------------------------------------------------------
-        // Refactored Logic (Skill-Centric for Global)
-        // ---------------------------------------------------------
+        // Size, hash and metadata come from the scan index unless the folder changed
+        let indexed = scan_index::lookup(&path, force, &mut result.index_stats)?;
+        let linked = symlinks::linked_target(dir, &path);
+
+        // Group into plugins: install provenance first, then a marketplace match by folder name.
+        // Skills imported from local directories and unknown skills stand alone.
+        let metadata = indexed.metadata.clone();
+        let fallback_description = if scope == "project" { "Project local skill" } else { "Locally detected skill" };
+        let (plugin_name, category, description, grouped_by) = if let Some(name) = metadata.plugin_name.clone() {
+            let plugin = marketplace.plugins.iter().find(|p| p.name == name);
+            (
+                name,
+                plugin.and_then(|p| p.category.clone()).or(metadata.category.clone()),
+                plugin.map(|p| p.description.clone()).or(metadata.description.clone()),
+                "provenance",
+            )
+        } else if let Some(plugin) = find_plugin_for_skill(&skill_name, marketplace)
+            .filter(|_| metadata.source_type.as_deref() != Some("LocalDirectory"))
+        {
+            (plugin.name, plugin.category, Some(plugin.description), "marketplace")
+        } else {
+            (
+                skill_name.clone(),
+                metadata.category.clone(),
+                metadata.description.clone().or_else(|| Some(fallback_description.to_string())),
+                "standalone",
+            )
+        };
 
-        let found_plugin_marketplace = find_plugin_for_skill(&skill_name, marketplace);
-        
-        // Project Scope: Match Marketplace OR read Local Metadata
-        if scope == "project" {
-            if let Some(plugin) = found_plugin_marketplace {
-               process_record(
-                   plugin.name, 
-                   plugin.category, 
-                   Some(plugin.description), 
-                   skill_name, 
-                   path, 
-                   scope, 
-                   project_path.clone(), 
-                   agent_id, 
-                   result
-               )?;
-            } else {
-                // If not found in marketplace, still try to identify as a local skill
-                let metadata = read_skill_metadata(&path);
-                process_record(
-                    skill_name.clone(),
-                    metadata.category,
-                    metadata.description.or_else(|| Some("Project local skill".to_string())),
-                    skill_name,
-                    path,
-                    scope,
-                    project_path.clone(),
-                    agent_id,
-                    result
-                )?;
+        result.skills.push(InstalledSkill {
+            name: skill_name.clone(),
+            plugin_name: plugin_name.clone(),
+            grouped_by: grouped_by.to_string(),
+            description: metadata.description.clone(),
+            category: metadata.category.clone(),
+            version: metadata.version.clone(),
+            agent_id: agent_id.to_string(),
+            scope: scope.to_string(),
+            project_path: project_path.clone(),
+            path: indexed.path.clone(),
+            size_bytes: indexed.size_bytes,
+            installed_at: indexed.installed_at.clone(),
+            content_hash: indexed.content_hash.clone(),
+            source_type: metadata.source_type.clone(),
+            link_target: linked.as_ref().map(|t| t.to_string_lossy().to_string()),
+        });
+
+        process_record(
+            plugin_name,
+            category,
+            description,
+            skill_name,
+            &indexed,
+            scope,
+            project_path.clone(),
+            agent_id,
+            result,
+        )?;
+
+        // Flag skills that are symlinks into another folder
+        if let Some(target) = linked {
+            let path_str = path.to_string_lossy().to_string();
+            if let Some(record) = result.plugins.iter_mut().find(|p| p.location.paths.contains(&path_str)) {
+                record.linked_paths.insert(path_str, target.to_string_lossy().to_string());
             }
-        } 
-        // Global Scope: Skill-Centric (Ignore Marketplace Grouping)
-        else if scope == "global" {
-            // Read metadata directly from skill.json
-            let metadata = read_skill_metadata(&path);
-            
-            // Use skill directory name as the "Plugin Name"
-            let display_name = skill_name.to_string(); 
-            
-            // Use metadata category or default to None (was "Global Skill")
-            let category = metadata.category;
-            
-            // Use metadata description or fallback
-            let description = metadata.description.or_else(|| {
-                Some("Locally detected skill".to_string())
-            });
-
-            process_record(
-                display_name,
-                category,
-                description,
-                skill_name,
-                path,
-                scope,
-                None, // Global has no project path
-                agent_id,
-                result
-            )?;
         }
     }
 
     Ok(())
 }
 
-// Helper to recursively find directories containing SKILL.md
-fn find_skill_dirs(dir: &PathBuf, depth: u32) -> Vec<PathBuf> {
+// Helper to recursively find directories containing SKILL.md.
+// Linked folders are followed (linked skills are common); links looping back up are not.
+pub fn find_skill_dirs(dir: &PathBuf, depth: u32) -> Vec<PathBuf> {
+    let mut guard = symlinks::LoopGuard::new();
+    guard.enter(dir);
+    find_skill_dirs_inner(dir, depth, &mut guard)
+}
+
+fn find_skill_dirs_inner(dir: &PathBuf, depth: u32, guard: &mut symlinks::LoopGuard) -> Vec<PathBuf> {
     let mut skills = Vec::new();
 
     if depth == 0 {
@@ -229,20 +291,21 @@ fn find_skill_dirs(dir: &PathBuf, depth: u32) -> Vec<PathBuf> {
     }
 
     if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_dir() {
-                    // Check if SKILL.md exists in this directory
-                    if path.join("SKILL.md").exists() {
-                        skills.push(path.clone());
-                        // If found a skill, we assume skills are not nested inside other skills
-                        // so we don't recurse further into this directory
-                    } else {
-                        // If not a skill itself, search inside
-                        skills.extend(find_skill_dirs(&path, depth - 1));
-                    }
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            // Check if SKILL.md exists in this directory
+            if path.join("SKILL.md").exists() {
+                // If found a skill, we assume skills are not nested inside other skills
+                // so we don't recurse further into this directory
+                if guard.descend(&path, |_| ()).is_some() {
+                    skills.push(path.clone());
                 }
+            } else if let Some(found) = guard.descend(&path, |g| find_skill_dirs_inner(&path, depth - 1, g)) {
+                // If not a skill itself, search inside
+                skills.extend(found);
             }
         }
     }
@@ -250,28 +313,103 @@ fn find_skill_dirs(dir: &PathBuf, depth: u32) -> Vec<PathBuf> {
     skills
 }
 
+/// Every installed skill folder: (skill dir, agent, scope, project path)
+pub fn installed_skill_dirs(scope: Option<&str>, project_path: Option<String>) -> Vec<(PathBuf, String, String, Option<String>)> {
+    let agents = agent_config::all_agents();
+    let mut roots = Vec::new();
+
+    if scope.is_none() || scope == Some("global") {
+        for agent in &agents {
+            if let Some(path) = agent_config::get_agent_global_path(&agent.id) {
+                roots.push((path, agent.id.clone(), "global".to_string(), None));
+            }
+        }
+    }
+    if scope.is_none() || scope == Some("project") {
+        let projects = match project_path {
+            Some(p) => vec![p],
+            None => project_discovery::known_project_paths(),
+        };
+        for project in projects {
+            for agent in &agents {
+                if let Some(path) = agent_config::get_agent_project_path(&agent.id, &project) {
+                    roots.push((path, agent.id.clone(), "project".to_string(), Some(project.clone())));
+                }
+            }
+        }
+    }
+
+    roots
+        .into_iter()
+        .filter(|(root, ..)| root.is_dir())
+        .flat_map(|(root, agent, scope, project)| {
+            find_skill_dirs(&root, 5)
+                .into_iter()
+                .map(move |dir| (dir, agent.clone(), scope.clone(), project.clone()))
+        })
+        .collect()
+}
+
+fn process_native_rule(
+    entry: &format_adapters::NativeRuleEntry,
+    force: bool,
+    result: &mut ScanResult,
+) -> Result<(), String> {
+    let indexed = scan_index::lookup(std::path::Path::new(&entry.path), force, &mut result.index_stats)?;
+    let source_meta = read_skill_metadata(std::path::Path::new(&entry.source_path));
+    result.skills.push(InstalledSkill {
+        name: entry.skill_name.clone(),
+        plugin_name: entry.plugin_name.clone(),
+        grouped_by: "provenance".to_string(),
+        description: source_meta.description.clone(),
+        category: source_meta.category.clone(),
+        version: source_meta.version.clone(),
+        agent_id: entry.agent_id.clone(),
+        scope: entry.scope.clone(),
+        project_path: entry.project_path.clone(),
+        path: indexed.path.clone(),
+        size_bytes: indexed.size_bytes,
+        installed_at: indexed.installed_at.clone(),
+        content_hash: indexed.content_hash.clone(),
+        source_type: Some(format!("NativeRule:{}", entry.format)),
+        link_target: None,
+    });
+    process_record(
+        entry.plugin_name.clone(),
+        source_meta.category,
+        source_meta
+            .description
+            .or_else(|| Some(format!("Converted {} rule", entry.format))),
+        entry.skill_name.clone(),
+        &indexed,
+        &entry.scope,
+        entry.project_path.clone(),
+        &entry.agent_id,
+        result,
+    )
+}
+
 fn process_record(
     plugin_name: String,
     category: Option<String>,
     description: Option<String>,
     skill_name: String,
-    path: PathBuf,
+    indexed: &scan_index::IndexedSkill,
     scope: &str,
     project_path: Option<String>,
     agent_id: &str,
     result: &mut ScanResult,
 ) -> Result<(), String> {
-    let meta = read_skill_metadata(&path);
-    let version = meta.version;
-    let install_time = get_install_time(&path)?;
-    let size = calculate_dir_size(&path)?;
-    let source_type = meta.source_type;
-
-    // Check if record exists (by Name)
-    let existing = result
-        .plugins
-        .iter_mut()
-        .find(|p| p.name == plugin_name);
+    let path = PathBuf::from(&indexed.path);
+    let version = indexed.metadata.version.clone();
+    let install_time = indexed.installed_at.clone();
+    let size = indexed.size_bytes;
+    let source_type = indexed.metadata.source_type.clone();
+
+    // Check if record exists (same plugin in the same scope/project)
+    let existing = result.plugins.iter_mut().find(|p| {
+        p.name == plugin_name && p.location.scope == scope && p.location.project_path == project_path
+    });
 
     if let Some(plugin_record) = existing {
         // Update agent list
@@ -311,6 +449,7 @@ fn process_record(
             size_bytes: size,
             paths_by_agent,
             source_type,
+            linked_paths: HashMap::new(),
         };
         result.plugins.push(installed);
     }
@@ -330,58 +469,51 @@ fn find_plugin_for_skill(
     skill_name: &str,
     marketplace: &marketplace::MarketplaceData,
 ) -> Option<marketplace::Plugin> {
-    marketplace.plugins.iter().find(|plugin| {
+    // 只比较 skill 路径的最后一级目录名（"./skills/my-pdf" 不匹配 "pdf"）
+    let mut matches = marketplace.plugins.iter().filter(|plugin| {
         plugin.skills.iter().any(|skill_path| {
-            // 检查skill路径是否以该skill_name结尾
-            skill_path.ends_with(skill_name) || skill_path.split('/').last().map_or(false, |n| n == skill_name)
+            skill_path.trim_end_matches('/').rsplit('/').next() == Some(skill_name)
         })
-    }).cloned()
-}
-
-fn get_install_time(dir: &std::path::Path) -> Result<String, String> {
-    let metadata = std::fs::metadata(dir).map_err(|e| e.to_string())?;
-    let modified = metadata.modified().map_err(|e| e.to_string())?;
-
-    // 转换为ISO 8601格式
-    let datetime: chrono::DateTime<chrono::Utc> = modified.into();
-    Ok(datetime.to_rfc3339())
+    });
+    let plugin = matches.next()?;
+    // A folder name shipped by several plugins doesn't say which one installed it
+    if matches.next().is_some() {
+        return None;
+    }
+    Some(plugin.clone())
 }
 
-struct SkillMetadata {
-    version: Option<String>,
-    description: Option<String>,
-    category: Option<String>,
-    source_type: Option<String>,
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SkillMetadata {
+    #[serde(default)]
+    pub name: Option<String>, // SKILL.md frontmatter name
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub source_type: Option<String>,
+    #[serde(default)]
+    pub plugin_name: Option<String>, // install provenance from .metadata.json
 }
 
-fn read_skill_metadata(dir: &std::path::Path) -> SkillMetadata {
+pub fn read_skill_metadata(dir: &std::path::Path) -> SkillMetadata {
     let mut metadata = SkillMetadata {
+        name: None,
         version: None,
         description: None,
         category: None,
         source_type: None,
+        plugin_name: None,
     };
 
     // 1. Try to read from SKILL.md (Frontmatter) first for rich description
     let skill_md_path = dir.join("SKILL.md");
     if skill_md_path.exists() {
-        if let Ok(content) = std::fs::read_to_string(&skill_md_path) {
-            // Simple frontmatter parser
-            let lines: Vec<&str> = content.lines().collect();
-            if lines.len() > 0 && lines[0].trim() == "---" {
-                for line in lines.iter().skip(1) {
-                    if line.trim() == "---" {
-                        break;
-                    }
-                    if let Some((key, value)) = line.split_once(':') {
-                        let key = key.trim();
-                        let value = value.trim().to_string();
-                        if key == "description" {
-                             metadata.description = Some(value);
-                        }
-                        // We could also extract name/category here if needed
-                    }
-                }
+        if let Ok(fm) = frontmatter::parse_file(&skill_md_path) {
+            if let Some(fm) = fm.frontmatter {
+                metadata.name = fm.name.map(|n| n.trim().to_string());
+                metadata.description = fm.description.map(|d| d.trim().to_string());
+                metadata.version = fm.version;
+                metadata.category = fm.metadata.get("category").and_then(|c| c.as_str()).map(str::to_string);
             }
         }
     }
@@ -391,8 +523,10 @@ fn read_skill_metadata(dir: &std::path::Path) -> SkillMetadata {
     if skill_json_path.exists() {
         if let Ok(content) = std::fs::read_to_string(&skill_json_path) {
             if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                if let Some(v) = json.get("version") {
-                    metadata.version = v.as_str().map(|s| s.to_string());
+                if metadata.version.is_none() {
+                    if let Some(v) = json.get("version") {
+                        metadata.version = v.as_str().map(|s| s.to_string());
+                    }
                 }
                 // Only use skill.json description if we haven't found one in SKILL.md
                 if metadata.description.is_none() {
@@ -401,7 +535,7 @@ fn read_skill_metadata(dir: &std::path::Path) -> SkillMetadata {
                     }
                 }
                 if let Some(c) = json.get("category") {
-                    metadata.category = c.as_str().map(|s| s.to_string());
+                    metadata.category = c.as_str().map(|s| s.to_string()).or(metadata.category.take());
                 }
             }
         }
@@ -415,6 +549,9 @@ fn read_skill_metadata(dir: &std::path::Path) -> SkillMetadata {
                 if let Some(st) = json.get("source_type") {
                     metadata.source_type = st.as_str().map(|s| s.to_string());
                 }
+                if let Some(pn) = json.get("plugin_name") {
+                    metadata.plugin_name = pn.as_str().map(|s| s.to_string());
+                }
             }
         }
     }
@@ -422,16 +559,7 @@ fn read_skill_metadata(dir: &std::path::Path) -> SkillMetadata {
     metadata
 }
 
-fn calculate_dir_size(dir: &std::path::Path) -> Result<u64, String> {
-    let mut size = 0u64;
-    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let metadata = entry.metadata().map_err(|e| e.to_string())?;
-        if metadata.is_dir() {
-            size += calculate_dir_size(&entry.path())?;
-        } else {
-            size += metadata.len();
-        }
-    }
-    Ok(size)
+/// Size of a skill folder with links followed, matching what an install copies and the scan index
+pub fn calculate_dir_size(dir: &std::path::Path) -> Result<u64, String> {
+    symlinks::dir_size(dir, symlinks::LinkPolicy::Follow).map_err(|e| e.to_string())
 }
@@ -0,0 +1,269 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use crate::commands::{agent_config, agent_detector, projects_registry, skill_watcher};
+use crate::types::{KnownProject, KnownProjectsFile, ProjectDiscoveryResult};
+
+pub fn get_known_projects_path() -> PathBuf {
+    dirs::config_dir()
+        .expect("Failed to get config directory")
+        .join("skillbox-studio")
+        .join("known-projects.json")
+}
+
+fn load_known_projects() -> Result<KnownProjectsFile, String> {
+    let path = get_known_projects_path();
+    if !path.exists() {
+        return Ok(KnownProjectsFile {
+            projects: vec![],
+            last_updated: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read known projects: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse known projects: {}", e))
+}
+
+fn save_known_projects(file: &KnownProjectsFile) -> Result<(), String> {
+    let path = get_known_projects_path();
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+        }
+    }
+    let content = serde_json::to_string_pretty(file).map_err(|e| format!("Failed to serialize known projects: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("Failed to write known projects: {}", e))
+}
+
+/// Every project we know about: registered or discovered by a workspace scan
+pub fn known_project_paths() -> Vec<String> {
+    let mut paths = projects_registry::registered_project_paths();
+    if let Ok(file) = load_known_projects() {
+        for p in file.projects {
+            if !paths.contains(&p.path) {
+                paths.push(p.path);
+            }
+        }
+    }
+    paths
+}
+
+/// Agents whose project skills directory exists under `dir`.
+/// Agents without a dedicated marker directory (plain `skills/`) are ignored, they match too much.
+fn agents_in_project(dir: &Path, agents: &[agent_config::AgentConfig]) -> Vec<String> {
+    agents
+        .iter()
+        .filter(|a| agent_detector::project_marker_for(a).is_some())
+        .filter(|a| dir.join(&a.project_path).is_dir())
+        .map(|a| a.id.clone())
+        .collect()
+}
+
+/// Bounded, .gitignore-aware crawl of the workspace roots
+fn crawl(
+    roots: &[PathBuf],
+    agents: &[agent_config::AgentConfig],
+    max_depth: u32,
+    max_directories: u32,
+) -> (Vec<KnownProject>, usize, bool) {
+    let mut found = Vec::new();
+    let mut visited = 0usize;
+    let mut truncated = false;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    'roots: for root in roots {
+        let walker = WalkBuilder::new(root)
+            .max_depth(Some(max_depth as usize))
+            .hidden(true) // project markers are checked directly, no need to enter dot-dirs
+            .git_ignore(true)
+            .git_global(false)
+            .require_git(false)
+            .follow_links(false)
+            .filter_entry(|e| {
+                let name = e.file_name().to_string_lossy();
+                !matches!(name.as_ref(), "node_modules" | "target" | "dist" | "build" | "__pycache__" | "vendor")
+            })
+            .build();
+
+        for entry in walker.flatten() {
+            if !entry.file_type().is_some_and(|t| t.is_dir()) {
+                continue;
+            }
+            visited += 1;
+            if visited > max_directories as usize {
+                truncated = true;
+                break 'roots;
+            }
+
+            let dir = entry.path();
+            let agents_found = agents_in_project(dir, agents);
+            if !agents_found.is_empty() {
+                found.push(KnownProject {
+                    path: dir.to_string_lossy().to_string(),
+                    agents: agents_found,
+                    source: "workspace_scan".to_string(),
+                    discovered_at: now.clone(),
+                });
+            }
+        }
+    }
+
+    (found, visited, truncated)
+}
+
+/// 扫描工作区根目录，发现包含代理项目 skills 目录的项目
+#[tauri::command]
+pub async fn discover_projects(
+    app: tauri::AppHandle,
+    roots: Option<Vec<String>>,
+) -> Result<ProjectDiscoveryResult, String> {
+    let started = std::time::Instant::now();
+    let config = crate::commands::config_manager::load_config(&app)?;
+    let settings = config.workspace;
+
+    let roots: Vec<PathBuf> = roots
+        .unwrap_or(settings.workspace_roots)
+        .iter()
+        .filter_map(|r| agent_config::resolve_path(r).ok())
+        .filter(|p| p.is_dir())
+        .collect();
+
+    if roots.is_empty() {
+        return Err("No existing workspace roots configured".to_string());
+    }
+
+    let agents = agent_config::all_agents();
+    let (found, visited, truncated) = crawl(&roots, &agents, settings.max_depth, settings.max_directories);
+
+    let mut file = load_known_projects()?;
+    let mut new_projects = 0;
+    for project in &found {
+        if let Some(existing) = file.projects.iter_mut().find(|p| p.path == project.path) {
+            existing.agents = project.agents.clone();
+        } else {
+            file.projects.push(project.clone());
+            new_projects += 1;
+        }
+    }
+    file.last_updated = chrono::Utc::now().to_rfc3339();
+    save_known_projects(&file)?;
+
+    // New project skill directories need to be watched too
+    if new_projects > 0 {
+        skill_watcher::refresh(&app);
+    }
+
+    Ok(ProjectDiscoveryResult {
+        projects_found: found,
+        new_projects,
+        directories_visited: visited,
+        truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// 已知项目：工作区扫描结果 + 已注册项目
+#[tauri::command]
+pub async fn list_known_projects() -> Result<Vec<KnownProject>, String> {
+    let mut projects = load_known_projects()?.projects;
+    let agents = agent_config::all_agents();
+    for path in projects_registry::registered_project_paths() {
+        if projects.iter().any(|p| p.path == path) {
+            continue;
+        }
+        projects.push(KnownProject {
+            agents: agents_in_project(Path::new(&path), &agents),
+            path,
+            source: "registered".to_string(),
+            discovered_at: String::new(),
+        });
+    }
+    Ok(projects)
+}
+
+#[tauri::command]
+pub async fn remove_known_project(path: String) -> Result<bool, String> {
+    let mut file = load_known_projects()?;
+    let before = file.projects.len();
+    file.projects.retain(|p| p.path != path);
+    if file.projects.len() == before {
+        return Ok(false);
+    }
+    file.last_updated = chrono::Utc::now().to_rfc3339();
+    save_known_projects(&file)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::agent_config::{AgentCapabilities, AgentConfig};
+
+    fn agents() -> Vec<AgentConfig> {
+        vec![AgentConfig {
+            id: "cursor".to_string(),
+            name: "Cursor".to_string(),
+            project_path: ".cursor/skills/".to_string(),
+            global_path: "~/.cursor/skills/".to_string(),
+            icon: "cursor".to_string(),
+            category: "Core".to_string(),
+            rule_format: None,
+            capabilities: AgentCapabilities::default(),
+        }]
+    }
+
+    fn project(root: &Path, rel: &str) {
+        fs::create_dir_all(root.join(rel).join(".cursor/skills")).unwrap();
+    }
+
+    fn found_paths(root: &Path, max_depth: u32, max_directories: u32) -> (Vec<String>, bool) {
+        let (found, _, truncated) = crawl(&[root.to_path_buf()], &agents(), max_depth, max_directories);
+        let mut paths: Vec<String> = found
+            .iter()
+            .map(|p| Path::new(&p.path).strip_prefix(root).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+        paths.sort();
+        (paths, truncated)
+    }
+
+    #[test]
+    fn test_crawl_respects_depth_and_ignore_rules() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        project(root, "app");
+        project(root, "work/api");
+        project(root, "a/b/c/deep");
+        project(root, "ignored/proj");
+        project(root, "node_modules/pkg");
+        project(root, "target/debug");
+        project(root, ".cache/proj");
+        fs::write(root.join(".gitignore"), "ignored/\n").unwrap();
+
+        let (paths, truncated) = found_paths(root, 2, 1000);
+        assert_eq!(paths, vec!["app", "work/api"]);
+        assert!(!truncated);
+
+        let (paths, _) = found_paths(root, 4, 1000);
+        assert_eq!(paths, vec!["a/b/c/deep", "app", "work/api"]);
+    }
+
+    #[test]
+    fn test_crawl_stops_at_directory_limit() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        for i in 0..10 {
+            project(root, &format!("p{}", i));
+        }
+
+        let (paths, truncated) = found_paths(root, 3, 1000);
+        assert_eq!(paths.len(), 10);
+        assert!(!truncated);
+
+        // The root itself counts as the first directory
+        let (paths, truncated) = found_paths(root, 3, 4);
+        assert_eq!(paths.len(), 3);
+        assert!(truncated);
+    }
+}
@@ -0,0 +1,273 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::commands::{install_history, skill_watcher};
+use crate::types::{ProjectsRegistry, RegisteredProject};
+
+pub fn get_registry_path() -> PathBuf {
+    dirs::config_dir()
+        .expect("Failed to get config directory")
+        .join("skillbox-studio")
+        .join("projects-registry.json")
+}
+
+fn ensure_registry_dir() -> Result<(), String> {
+    let path = get_registry_path();
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+fn default_project_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+/// Registered paths are compared without trailing separators or `.` components
+fn normalize_path(path: &str) -> String {
+    let normalized: PathBuf = Path::new(path).components().collect();
+    if normalized.as_os_str().is_empty() {
+        return path.to_string();
+    }
+    normalized.to_string_lossy().to_string()
+}
+
+fn project_index(registry: &ProjectsRegistry, path: &str) -> Option<usize> {
+    let path = normalize_path(path);
+    registry.registered_projects.iter().position(|p| normalize_path(&p.path) == path)
+}
+
+fn load_registry() -> Result<ProjectsRegistry, String> {
+    load_registry_from(&get_registry_path())
+}
+
+fn load_registry_from(path: &Path) -> Result<ProjectsRegistry, String> {
+    if !path.exists() {
+        return Ok(ProjectsRegistry {
+            registered_projects: vec![],
+            last_updated: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read projects registry: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse projects registry: {}", e))
+}
+
+fn save_registry(registry: &ProjectsRegistry) -> Result<(), String> {
+    ensure_registry_dir()?;
+    let path = get_registry_path();
+    let content = serde_json::to_string_pretty(registry).map_err(|e| format!("Failed to serialize projects registry: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("Failed to write projects registry: {}", e))
+}
+
+/// One-time migration at startup: projects used to be inferred from install history,
+/// so a missing registry is seeded from it
+pub fn import_install_history() -> Result<(), String> {
+    if get_registry_path().exists() {
+        return Ok(());
+    }
+    let mut registry = load_registry()?;
+    for path in install_history::installed_project_paths() {
+        if Path::new(&path).is_dir() {
+            add_project(&mut registry, &path, None, vec![], vec![]);
+        }
+    }
+    save_registry(&registry)
+}
+
+/// Add a project unless it is already registered. `false` when it was.
+fn add_project(
+    registry: &mut ProjectsRegistry,
+    path: &str,
+    name: Option<String>,
+    default_agents: Vec<String>,
+    tags: Vec<String>,
+) -> bool {
+    if project_index(registry, path).is_some() {
+        return false;
+    }
+    let path = normalize_path(path);
+    registry.registered_projects.push(RegisteredProject {
+        name: name.unwrap_or_else(|| default_project_name(Path::new(&path))),
+        path,
+        default_agents,
+        tags,
+    });
+    registry.last_updated = chrono::Utc::now().to_rfc3339();
+    true
+}
+
+/// Paths of all registered projects
+pub fn registered_project_paths() -> Vec<String> {
+    load_registry()
+        .map(|r| r.registered_projects.into_iter().map(|p| p.path).collect())
+        .unwrap_or_default()
+}
+
+/// Agents to install into for a project-scope operation: the explicit selection,
+/// or the project's default agents when nothing was selected.
+pub fn resolve_install_agents(scope: &str, scope_path: Option<&str>, agents: &[String]) -> Vec<String> {
+    if !agents.is_empty() || scope != "project" {
+        return agents.to_vec();
+    }
+    let Some(path) = scope_path else {
+        return Vec::new();
+    };
+    load_registry().map(|r| default_agents_for(&r, path)).unwrap_or_default()
+}
+
+fn default_agents_for(registry: &ProjectsRegistry, path: &str) -> Vec<String> {
+    project_index(registry, path)
+        .map(|i| registry.registered_projects[i].default_agents.clone())
+        .unwrap_or_default()
+}
+
+/// Register a project after something was installed into it, so scans keep finding it
+pub fn ensure_registered(path: &str) -> Result<(), String> {
+    let mut registry = load_registry()?;
+    if !add_project(&mut registry, path, None, vec![], vec![]) {
+        return Ok(());
+    }
+    save_registry(&registry)
+}
+
+#[tauri::command]
+pub async fn register_project(
+    app: tauri::AppHandle,
+    path: String,
+    name: Option<String>,
+    default_agents: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+) -> Result<bool, String> {
+    let mut registry = load_registry()?;
+
+    let path_buf = PathBuf::from(&path);
+    if !path_buf.is_dir() {
+        return Err("Directory does not exist".to_string());
+    }
+
+    if !add_project(&mut registry, &path, name, default_agents.unwrap_or_default(), tags.unwrap_or_default()) {
+        return Ok(false); // Already registered
+    }
+    save_registry(&registry)?;
+    skill_watcher::refresh(&app);
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn unregister_project(app: tauri::AppHandle, path: String) -> Result<bool, String> {
+    let mut registry = load_registry()?;
+
+    if let Some(index) = project_index(&registry, &path) {
+        registry.registered_projects.remove(index);
+        registry.last_updated = chrono::Utc::now().to_rfc3339();
+        save_registry(&registry)?;
+        skill_watcher::refresh(&app);
+        Ok(true)
+    } else {
+        Ok(false) // Not found
+    }
+}
+
+#[tauri::command]
+pub async fn list_registered_projects(tag: Option<String>) -> Result<Vec<RegisteredProject>, String> {
+    Ok(with_tag(load_registry()?.registered_projects, tag.as_deref()))
+}
+
+fn with_tag(mut projects: Vec<RegisteredProject>, tag: Option<&str>) -> Vec<RegisteredProject> {
+    if let Some(tag) = tag {
+        projects.retain(|p| p.tags.iter().any(|t| t == tag));
+    }
+    projects
+}
+
+#[tauri::command]
+pub async fn update_project(
+    path: String,
+    name: Option<String>,
+    default_agents: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+) -> Result<bool, String> {
+    let mut registry = load_registry()?;
+    if !update_entry(&mut registry, &path, name, default_agents, tags) {
+        return Ok(false);
+    }
+    save_registry(&registry)?;
+    Ok(true)
+}
+
+/// Change the given fields of a registered project. `false` when it isn't registered.
+fn update_entry(
+    registry: &mut ProjectsRegistry,
+    path: &str,
+    name: Option<String>,
+    default_agents: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+) -> bool {
+    let Some(index) = project_index(registry, path) else {
+        return false;
+    };
+    let project = &mut registry.registered_projects[index];
+    if let Some(name) = name {
+        project.name = name;
+    }
+    if let Some(agents) = default_agents {
+        project.default_agents = agents;
+    }
+    if let Some(tags) = tags {
+        project.tags = tags;
+    }
+    registry.last_updated = chrono::Utc::now().to_rfc3339();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> ProjectsRegistry {
+        ProjectsRegistry { registered_projects: vec![], last_updated: String::new() }
+    }
+
+    #[test]
+    fn test_register_and_update_ignore_trailing_separators() {
+        let mut r = registry();
+        assert!(add_project(&mut r, "/work/app/", None, vec![], vec!["web".to_string()]));
+        assert!(!add_project(&mut r, "/work/app", None, vec![], vec![]));
+        assert!(!add_project(&mut r, "/work/./app", None, vec![], vec![]));
+        assert_eq!(r.registered_projects.len(), 1);
+        assert_eq!(r.registered_projects[0].path, "/work/app");
+        assert_eq!(r.registered_projects[0].name, "app");
+
+        assert!(update_entry(&mut r, "/work/app/", Some("App".to_string()), Some(vec!["cursor".to_string()]), None));
+        assert!(!update_entry(&mut r, "/work/other", Some("Other".to_string()), None, None));
+        let project = &r.registered_projects[0];
+        assert_eq!((project.name.as_str(), project.tags.as_slice()), ("App", ["web".to_string()].as_slice()));
+        assert_eq!(default_agents_for(&r, "/work/app/"), vec!["cursor"]);
+        assert!(default_agents_for(&r, "/work/other").is_empty());
+    }
+
+    #[test]
+    fn test_tag_filter() {
+        let mut r = registry();
+        add_project(&mut r, "/a", None, vec![], vec!["web".to_string()]);
+        add_project(&mut r, "/b", None, vec![], vec!["cli".to_string(), "web".to_string()]);
+        add_project(&mut r, "/c", None, vec![], vec![]);
+        let paths = |tag| with_tag(r.registered_projects.clone(), tag).into_iter().map(|p| p.path).collect::<Vec<_>>();
+        assert_eq!(paths(Some("web")), vec!["/a", "/b"]);
+        assert_eq!(paths(Some("cli")), vec!["/b"]);
+        assert_eq!(paths(None).len(), 3);
+    }
+
+    #[test]
+    fn test_reading_a_missing_registry_writes_nothing() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("projects-registry.json");
+        assert!(load_registry_from(&path).unwrap().registered_projects.is_empty());
+        assert!(!path.exists());
+    }
+}
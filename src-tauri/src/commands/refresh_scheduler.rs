@@ -0,0 +1,282 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+use crate::commands::{config_manager, git_manager};
+use crate::types::{RefreshSettings, UpdateResult};
+
+/// Event emitted to the frontend after every background or manual refresh
+pub const REFRESH_EVENT: &str = "marketplace-refresh";
+
+const TICK: Duration = Duration::from_secs(60);
+const STARTUP_DELAY: Duration = Duration::from_secs(15);
+const MAX_BACKOFF: Duration = Duration::from_secs(48 * 60 * 60);
+
+// 跳过的仓库及原因
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkippedRepo {
+    pub repo_id: String,
+    pub repo_name: String,
+    pub reason: String, // "pinned" | "not_git" | "backoff"
+    pub retry_after: Option<String>,
+}
+
+// 一次刷新的汇总（事件负载）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefreshSummary {
+    pub trigger: String, // "startup" | "interval" | "manual"
+    pub started_at: String,
+    pub finished_at: String,
+    pub results: Vec<UpdateResult>,
+    pub skipped: Vec<SkippedRepo>,
+    pub updated_count: usize, // repositories that received new commits
+    pub failed_count: usize,
+}
+
+// 连续失败的仓库
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepoBackoff {
+    pub repo_id: String,
+    pub failures: u32,
+    pub retry_after: String,
+    pub last_error: String,
+}
+
+// 调度器状态
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefreshStatus {
+    pub running: bool,
+    pub last_run: Option<String>,
+    pub next_run: Option<String>, // None while the schedule is disabled
+    pub backoff: Vec<RepoBackoff>,
+}
+
+struct SchedulerState {
+    started_at: DateTime<Utc>,
+    last_run: Option<DateTime<Utc>>,
+    backoff: HashMap<String, (u32, DateTime<Utc>, String)>, // repo id -> (failures, retry after, last error)
+}
+
+static STATE: Lazy<Mutex<SchedulerState>> = Lazy::new(|| {
+    Mutex::new(SchedulerState {
+        started_at: Utc::now(),
+        last_run: None,
+        backoff: HashMap::new(),
+    })
+});
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static STARTED: AtomicBool = AtomicBool::new(false);
+static LAST_ACTIVITY: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+
+/// Note that the user is doing something; background refreshes wait for a quiet moment
+pub fn mark_activity() {
+    if let Ok(mut last) = LAST_ACTIVITY.lock() {
+        *last = Instant::now();
+    }
+}
+
+fn is_idle(settings: &RefreshSettings) -> bool {
+    let idle_for = Duration::from_secs(u64::from(settings.idle_minutes) * 60);
+    LAST_ACTIVITY.lock().map(|last| last.elapsed() >= idle_for).unwrap_or(true)
+}
+
+fn interval(settings: &RefreshSettings) -> chrono::Duration {
+    chrono::Duration::minutes(i64::from(settings.interval_minutes.max(1)))
+}
+
+/// Wait after `failures` consecutive failures: the interval, doubled per failure, capped at 48 hours
+fn backoff_delay(interval: chrono::Duration, failures: u32) -> chrono::Duration {
+    let cap = chrono::Duration::from_std(MAX_BACKOFF).unwrap_or(chrono::Duration::MAX);
+    let factor = 2i32.saturating_pow(failures.min(16));
+    interval.checked_mul(factor).map_or(cap, |d| d.min(cap))
+}
+
+fn next_run(settings: &RefreshSettings) -> Option<DateTime<Utc>> {
+    if !settings.enabled {
+        return None;
+    }
+    let state = STATE.lock().ok()?;
+    Some(state.last_run.unwrap_or(state.started_at) + interval(settings))
+}
+
+/// Held while repositories are being updated; scheduled refreshes and manual updates
+/// share it so two of them never pull the same clone at once
+pub struct UpdateLock(());
+
+impl Drop for UpdateLock {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+pub fn lock_updates() -> Result<UpdateLock, String> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("Repositories are already being updated".to_string());
+    }
+    Ok(UpdateLock(()))
+}
+
+/// Refresh every enabled repository that isn't pinned or backing off, then emit the summary
+pub async fn run_refresh(app: &tauri::AppHandle, trigger: &str) -> Result<RefreshSummary, String> {
+    let lock = lock_updates()?;
+    let result = refresh_repositories(app, trigger).await;
+    drop(lock);
+
+    let summary = result?;
+    if let Err(e) = app.emit(REFRESH_EVENT, &summary) {
+        eprintln!("Failed to emit refresh summary: {}", e);
+    }
+    Ok(summary)
+}
+
+async fn refresh_repositories(app: &tauri::AppHandle, trigger: &str) -> Result<RefreshSummary, String> {
+    let started_at = Utc::now();
+    let config = config_manager::load_config(app)?;
+    let step = interval(&config.refresh);
+
+    let mut results = Vec::new();
+    let mut skipped = Vec::new();
+    for repo in config.marketplace.repositories.iter().filter(|r| r.enabled) {
+        let skip = |reason: &str, retry_after: Option<String>| SkippedRepo {
+            repo_id: repo.id.clone(),
+            repo_name: repo.name.clone(),
+            reason: reason.to_string(),
+            retry_after,
+        };
+        if repo.pinned {
+            skipped.push(skip("pinned", None));
+            continue;
+        }
+        if !Path::new(&repo.local_path).join(".git").exists() {
+            skipped.push(skip("not_git", None));
+            continue;
+        }
+        let retry_after = STATE.lock().ok().and_then(|s| s.backoff.get(&repo.id).map(|b| b.1));
+        if let Some(at) = retry_after.filter(|at| *at > Utc::now()) {
+            skipped.push(skip("backoff", Some(at.to_rfc3339())));
+            continue;
+        }
+
+        let result = git_manager::update_repository(app, repo)
+            .await
+            .unwrap_or_else(|e| git_manager::failed_update(repo, e));
+        if let Ok(mut state) = STATE.lock() {
+            if result.success {
+                state.backoff.remove(&repo.id);
+            } else {
+                let failures = state.backoff.get(&repo.id).map_or(0, |b| b.0) + 1;
+                let error = result.error.clone().unwrap_or_default();
+                state.backoff.insert(repo.id.clone(), (failures, Utc::now() + backoff_delay(step, failures), error));
+            }
+        }
+        results.push(result);
+    }
+
+    git_manager::record_updates(app, &results)?;
+    if let Ok(mut state) = STATE.lock() {
+        state.last_run = Some(started_at);
+    }
+
+    Ok(RefreshSummary {
+        trigger: trigger.to_string(),
+        started_at: started_at.to_rfc3339(),
+        finished_at: Utc::now().to_rfc3339(),
+        updated_count: results.iter().filter(|r| !r.commits.is_empty()).count(),
+        failed_count: results.iter().filter(|r| !r.success).count(),
+        results,
+        skipped,
+    })
+}
+
+/// Start the background loop once. Settings are re-read on every tick, so changes apply without a restart.
+/// `on_startup` refreshes once shortly after launch, even when the periodic schedule is off.
+pub fn start(app: &tauri::AppHandle) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let on_startup = config_manager::load_config(&app).map(|c| c.refresh.on_startup).unwrap_or(false);
+        if on_startup {
+            tokio::time::sleep(STARTUP_DELAY).await;
+            if let Err(e) = run_refresh(&app, "startup").await {
+                eprintln!("Startup refresh failed: {}", e);
+            }
+        }
+
+        loop {
+            tokio::time::sleep(TICK).await;
+            let Ok(config) = config_manager::load_config(&app) else {
+                continue;
+            };
+            let settings = config.refresh;
+            if next_run(&settings).is_none_or(|at| at > Utc::now()) {
+                continue;
+            }
+            if settings.only_when_idle && !is_idle(&settings) {
+                continue; // try again on the next tick
+            }
+            if let Err(e) = run_refresh(&app, "interval").await {
+                eprintln!("Scheduled refresh failed: {}", e);
+            }
+        }
+    });
+}
+
+/// 立即刷新所有（未固定的）仓库
+#[tauri::command]
+pub async fn refresh_marketplaces_now(app: tauri::AppHandle) -> Result<RefreshSummary, String> {
+    run_refresh(&app, "manual").await
+}
+
+/// 获取后台刷新状态
+#[tauri::command]
+pub fn get_refresh_status(app: tauri::AppHandle) -> Result<RefreshStatus, String> {
+    let settings = config_manager::load_config(&app)?.refresh;
+    let next = next_run(&settings);
+    let state = STATE.lock().map_err(|e| e.to_string())?;
+    let mut backoff: Vec<RepoBackoff> = state
+        .backoff
+        .iter()
+        .map(|(id, (failures, retry_after, error))| RepoBackoff {
+            repo_id: id.clone(),
+            failures: *failures,
+            retry_after: retry_after.to_rfc3339(),
+            last_error: error.clone(),
+        })
+        .collect();
+    backoff.sort_by(|a, b| a.repo_id.cmp(&b.repo_id));
+
+    Ok(RefreshStatus {
+        running: RUNNING.load(Ordering::SeqCst),
+        last_run: state.last_run.map(|t| t.to_rfc3339()),
+        next_run: next.map(|t| t.to_rfc3339()),
+        backoff,
+    })
+}
+
+/// 前端报告用户活动，用于“仅空闲时刷新”
+#[tauri::command]
+pub fn report_user_activity() {
+    mark_activity();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_is_capped() {
+        let hour = chrono::Duration::hours(1);
+        assert_eq!(backoff_delay(hour, 1), chrono::Duration::hours(2));
+        assert_eq!(backoff_delay(hour, 3), chrono::Duration::hours(8));
+        assert_eq!(backoff_delay(hour, 10), chrono::Duration::hours(48));
+        assert_eq!(backoff_delay(hour, u32::MAX), chrono::Duration::hours(48));
+    }
+}
@@ -0,0 +1,352 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use crate::commands::plugin_scanner::{self, SkillMetadata};
+use crate::commands::symlinks;
+
+// Bump whenever the stamp or the cached entry changes shape or meaning
+const INDEX_VERSION: &str = "4";
+
+/// Change detector for a skill folder: the relative path, mtime and length of every file
+/// in it, links followed as an install would. Only metadata is read, never file contents,
+/// so an in-place edit anywhere in the folder changes the stamp without hashing it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Stamp {
+    pub dir_mtime: u64,
+    pub files: Vec<(String, u64, u64)>, // (relative path, mtime, length)
+}
+
+// 索引中的单个 skill
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexedSkill {
+    pub path: String,
+    pub stamp: Stamp,
+    pub content_hash: String,
+    pub size_bytes: u64,
+    pub installed_at: String,
+    pub metadata: SkillMetadata,
+}
+
+// scan-index.json 文件格式
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct IndexFile {
+    version: String,
+    entries: HashMap<String, IndexedSkill>,
+    last_updated: String,
+}
+
+struct IndexState {
+    file: IndexFile,
+    loaded: bool,
+    dirty: bool,
+}
+
+static INDEX: Lazy<Mutex<IndexState>> = Lazy::new(|| {
+    Mutex::new(IndexState {
+        file: IndexFile::default(),
+        loaded: false,
+        dirty: false,
+    })
+});
+
+// 单次扫描的索引命中统计
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct IndexStats {
+    pub cache_hits: usize,
+    pub cache_misses: usize,
+}
+
+fn get_index_path() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
+    let config_dir = home_dir.join(".skillbox-studio");
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+    }
+    Ok(config_dir.join("scan-index.json"))
+}
+
+fn ensure_loaded(state: &mut IndexState) {
+    if state.loaded {
+        return;
+    }
+    state.loaded = true;
+    // A missing or unreadable index is just an empty one; it will be rebuilt
+    if let Ok(path) = get_index_path() {
+        if let Ok(content) = fs::read_to_string(path) {
+            if let Ok(file) = serde_json::from_str::<IndexFile>(&content) {
+                if file.version == INDEX_VERSION {
+                    state.file = file;
+                }
+            }
+        }
+    }
+}
+
+fn mtime_millis(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn compute_stamp(path: &Path) -> Result<Stamp, String> {
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    let mut stamp = Stamp {
+        dir_mtime: mtime_millis(&meta),
+        ..Default::default()
+    };
+    for (rel, file) in list_files(path, true)? {
+        if let Ok(m) = fs::metadata(&file) {
+            stamp.files.push((rel, mtime_millis(&m), m.len()));
+        }
+    }
+    stamp.files.sort();
+    Ok(stamp)
+}
+
+/// Files of a skill folder (or the rule file itself) by relative path. Links are followed,
+/// loops and dangling links skipped. `with_bookkeeping` keeps the installer's `.metadata.json`.
+fn list_files(path: &Path, with_bookkeeping: bool) -> Result<Vec<(String, PathBuf)>, String> {
+    if path.is_file() {
+        return Ok(vec![(String::new(), path.to_path_buf())]);
+    }
+    let mut guard = symlinks::LoopGuard::new();
+    guard.enter(path);
+    collect_files(path, &mut guard, with_bookkeeping, &mut HashMap::new()).map_err(|e| e.to_string())
+}
+
+/// SHA-256 over every file's relative path and content, in a stable order.
+/// Also returns the total size so the folder is only walked once.
+pub fn hash_skill_contents(path: &Path) -> Result<(String, u64), String> {
+    let mut files = list_files(path, false)?;
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    for (rel, file) in files {
+        let bytes = fs::read(&file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        size += bytes.len() as u64;
+        hasher.update(rel.as_bytes());
+        hasher.update([0u8]);
+        hasher.update(&bytes);
+    }
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
+
+// 已列出的真实目录：菱形链接只读取一次
+type ListingMemo = HashMap<PathBuf, Vec<(String, PathBuf)>>;
+
+/// Files below `dir` by path relative to it
+fn collect_files(
+    dir: &Path,
+    guard: &mut symlinks::LoopGuard,
+    with_bookkeeping: bool,
+    memo: &mut ListingMemo,
+) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut out = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        // Same content as an install would copy: links followed, loops and dangling links skipped
+        let Ok(meta) = fs::metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            let files = subdir_files(&path, guard, with_bookkeeping, memo)?;
+            out.extend(files.into_iter().map(|(rel, file)| (format!("{}/{}", name, rel), file)));
+        } else {
+            // Installer bookkeeping doesn't count as skill content
+            if !with_bookkeeping && name == ".metadata.json" {
+                continue;
+            }
+            guard.count(1)?;
+            out.push((name, path));
+        }
+    }
+    Ok(out)
+}
+
+/// Files of a folder met during the walk. A real folder listed before through another path
+/// isn't read again, but its files still count against the walk's budget.
+fn subdir_files(
+    path: &Path,
+    guard: &mut symlinks::LoopGuard,
+    with_bookkeeping: bool,
+    memo: &mut ListingMemo,
+) -> io::Result<Vec<(String, PathBuf)>> {
+    let real = fs::canonicalize(path).ok();
+    if let Some(files) = real.as_ref().and_then(|r| memo.get(r)) {
+        guard.count(files.len())?;
+        return Ok(files.clone());
+    }
+    let cuts = guard.cuts();
+    let Some(files) = guard.descend(path, |g| collect_files(path, g, with_bookkeeping, memo)).transpose()? else {
+        return Ok(Vec::new());
+    };
+    // A listing that depended on a cut loop would differ when reached another way
+    if let Some(real) = real.filter(|_| guard.cuts() == cuts) {
+        memo.insert(real, files.clone());
+    }
+    Ok(files)
+}
+
+fn build_entry(path: &Path, stamp: Stamp) -> Result<IndexedSkill, String> {
+    let (content_hash, size_bytes) = hash_skill_contents(path)?;
+    let metadata = plugin_scanner::read_skill_metadata(path);
+
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    let modified = meta.modified().map_err(|e| e.to_string())?;
+    let installed_at: chrono::DateTime<chrono::Utc> = modified.into();
+
+    Ok(IndexedSkill {
+        path: path.to_string_lossy().to_string(),
+        stamp,
+        content_hash,
+        size_bytes,
+        installed_at: installed_at.to_rfc3339(),
+        metadata,
+    })
+}
+
+/// Return the indexed data for a skill folder (or converted rule file),
+/// re-reading it from disk only when its stamp changed.
+pub fn lookup(path: &Path, force: bool, stats: &mut IndexStats) -> Result<IndexedSkill, String> {
+    let key = path.to_string_lossy().to_string();
+    let stamp = compute_stamp(path)?;
+
+    let mut state = INDEX.lock().map_err(|_| "Scan index lock poisoned".to_string())?;
+    ensure_loaded(&mut state);
+
+    if !force {
+        if let Some(entry) = state.file.entries.get(&key) {
+            if entry.stamp == stamp {
+                stats.cache_hits += 1;
+                return Ok(entry.clone());
+            }
+        }
+    }
+
+    let entry = build_entry(path, stamp)?;
+    stats.cache_misses += 1;
+    state.file.entries.insert(key, entry.clone());
+    state.dirty = true;
+    Ok(entry)
+}
+
+/// Indexed skill folders that are `path` itself or one of its ancestors
+pub fn indexed_ancestors(path: &Path) -> Vec<PathBuf> {
+    let Ok(mut state) = INDEX.lock() else {
+        return Vec::new();
+    };
+    ensure_loaded(&mut state);
+    state
+        .file
+        .entries
+        .keys()
+        .map(PathBuf::from)
+        .filter(|k| path.starts_with(k))
+        .collect()
+}
+
+/// Drop the entry for a path, e.g. after its skill was deleted.
+pub fn invalidate(path: &Path) {
+    if let Ok(mut state) = INDEX.lock() {
+        ensure_loaded(&mut state);
+        if state.file.entries.remove(path.to_string_lossy().as_ref()).is_some() {
+            state.dirty = true;
+        }
+    }
+}
+
+/// Remove entries for folders that no longer exist and write the index if it changed.
+pub fn flush() -> Result<(), String> {
+    let mut state = INDEX.lock().map_err(|_| "Scan index lock poisoned".to_string())?;
+    ensure_loaded(&mut state);
+
+    let before = state.file.entries.len();
+    state.file.entries.retain(|k, _| Path::new(k).exists());
+    if state.file.entries.len() != before {
+        state.dirty = true;
+    }
+    if !state.dirty {
+        return Ok(());
+    }
+
+    state.file.version = INDEX_VERSION.to_string();
+    state.file.last_updated = chrono::Utc::now().to_rfc3339();
+    let content = serde_json::to_string(&state.file)
+        .map_err(|e| format!("Failed to serialize scan index: {}", e))?;
+    fs::write(get_index_path()?, content).map_err(|e| format!("Failed to write scan index: {}", e))?;
+    state.dirty = false;
+    Ok(())
+}
+
+/// 清空扫描索引，下次扫描将完整重建
+#[tauri::command]
+pub fn clear_scan_index() -> Result<usize, String> {
+    let mut state = INDEX.lock().map_err(|_| "Scan index lock poisoned".to_string())?;
+    ensure_loaded(&mut state);
+    let removed = state.file.entries.len();
+    state.file.entries.clear();
+    state.dirty = false;
+
+    let path = get_index_path()?;
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| format!("Failed to remove scan index: {}", e))?;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stamp_sees_nested_edits() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("references/deep")).unwrap();
+        fs::write(root.join("SKILL.md"), "---\nname: a\n---\n").unwrap();
+        fs::write(root.join("references/deep/x.md"), "one").unwrap();
+        let before = compute_stamp(root).unwrap();
+        assert_eq!(before.files.len(), 2);
+
+        fs::write(root.join("references/deep/x.md"), "one two").unwrap();
+        assert_ne!(compute_stamp(root).unwrap(), before);
+
+        // Bookkeeping changes the stamp (it carries provenance) but not the content hash
+        let (hash, _) = hash_skill_contents(root).unwrap();
+        fs::write(root.join(".metadata.json"), "{}").unwrap();
+        assert_eq!(compute_stamp(root).unwrap().files.len(), 3);
+        assert_eq!(hash_skill_contents(root).unwrap().0, hash);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_diamond_of_links_hits_the_budget() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        for i in 0..=30 {
+            let level = root.join(format!("levels/{}", i));
+            fs::create_dir_all(&level).unwrap();
+            fs::write(level.join("f"), "x").unwrap();
+            if i > 0 {
+                let prev = root.join(format!("levels/{}", i - 1));
+                std::os::unix::fs::symlink(&level, prev.join("a")).unwrap();
+                std::os::unix::fs::symlink(&level, prev.join("b")).unwrap();
+            }
+        }
+        // 2^31 paths: fails on the budget instead of listing them
+        let err = hash_skill_contents(&root.join("levels/0")).unwrap_err();
+        assert!(err.contains("a link probably points outside the skill"));
+    }
+}
@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::commands::{marketplace, plugin_scanner, scan_index};
+
+// 同名 skill 的一份已安装副本
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillCopy {
+    pub path: String,
+    pub agents: Vec<String>, // several agents can share one directory
+    pub scope: String, // "global" | "project"
+    pub project_path: Option<String>,
+    pub content_hash: String,
+    pub size_bytes: u64,
+    pub variant: usize, // copies with the same content share a variant number
+}
+
+// 某个代理在某个上下文中实际加载的副本
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillResolution {
+    pub agent_id: String,
+    pub project_path: Option<String>, // None: outside any project, only global skills apply
+    pub effective_path: String,
+    pub shadowed_paths: Vec<String>,
+    pub ambiguous: bool, // several copies in the same scope, which one wins depends on the agent
+}
+
+// 按名称分组的冲突
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillConflictGroup {
+    pub skill_name: String,
+    pub copies: Vec<SkillCopy>,
+    pub variants: usize,
+    pub diverged: bool, // copies with different content exist
+    pub marketplace_plugins: Vec<String>, // plugins shipping a skill folder with this name
+    pub resolutions: Vec<SkillResolution>,
+}
+
+// 冲突分析报告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConflictReport {
+    pub skills_checked: usize,
+    pub groups: Vec<SkillConflictGroup>,
+    pub diverged_count: usize,
+    pub shadowed_count: usize,
+    pub duration_ms: u64,
+}
+
+fn skill_folder_name(path: &str) -> String {
+    Path::new(path.trim_start_matches("./"))
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Which copy each agent sees: a project copy shadows the global one, per agent
+fn resolve(copies: &[SkillCopy]) -> Vec<SkillResolution> {
+    let mut agents: Vec<&String> = copies.iter().flat_map(|c| &c.agents).collect();
+    agents.sort();
+    agents.dedup();
+    let mut projects: Vec<&String> = copies.iter().filter_map(|c| c.project_path.as_ref()).collect();
+    projects.sort();
+    projects.dedup();
+
+    let mut resolutions = Vec::new();
+    for agent in agents {
+        let visible = |scope: &str, project: Option<&String>| -> Vec<&SkillCopy> {
+            copies
+                .iter()
+                .filter(|c| c.agents.contains(agent) && c.scope == scope && c.project_path.as_ref() == project)
+                .collect()
+        };
+        let global = visible("global", None);
+
+        let mut push = |project: Option<&String>, winners: &[&SkillCopy], shadowed: &[&SkillCopy]| {
+            let Some(first) = winners.first() else {
+                return;
+            };
+            resolutions.push(SkillResolution {
+                agent_id: agent.clone(),
+                project_path: project.cloned(),
+                effective_path: first.path.clone(),
+                shadowed_paths: winners[1..].iter().chain(shadowed).map(|c| c.path.clone()).collect(),
+                ambiguous: winners.len() > 1,
+            });
+        };
+
+        push(None, &global, &[]);
+        for project in &projects {
+            let local = visible("project", Some(project));
+            if local.is_empty() {
+                push(Some(project), &global, &[]);
+            } else {
+                push(Some(project), &local, &global);
+            }
+        }
+    }
+    resolutions
+}
+
+/// 分析同名 skill：跨作用域/代理的重复、遮蔽与内容分歧
+#[tauri::command]
+pub fn analyze_skill_conflicts(
+    app: tauri::AppHandle,
+    scope: Option<String>,
+    project_path: Option<String>,
+) -> Result<ConflictReport, String> {
+    let started = std::time::Instant::now();
+    let mut stats = scan_index::IndexStats::default();
+    let mut by_name: BTreeMap<String, Vec<SkillCopy>> = BTreeMap::new();
+    let mut checked = 0;
+
+    for (dir, agent_id, scope, project) in plugin_scanner::installed_skill_dirs(scope.as_deref(), project_path) {
+        let path = dir.to_string_lossy().to_string();
+        let name = skill_folder_name(&path);
+        let copies = by_name.entry(name).or_default();
+        if let Some(existing) = copies.iter_mut().find(|c| c.path == path) {
+            if !existing.agents.contains(&agent_id) {
+                existing.agents.push(agent_id);
+            }
+            continue;
+        }
+
+        checked += 1;
+        let indexed = scan_index::lookup(&dir, false, &mut stats)?;
+        copies.push(SkillCopy {
+            path,
+            agents: vec![agent_id],
+            scope,
+            project_path: project,
+            content_hash: indexed.content_hash,
+            size_bytes: indexed.size_bytes,
+            variant: 0,
+        });
+    }
+    if let Err(e) = scan_index::flush() {
+        eprintln!("Failed to save scan index: {}", e);
+    }
+
+    // Two plugins shipping the same folder name collide as soon as both are installed
+    let mut shipped_by: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    if let Ok(data) = marketplace::get_marketplace_data_cached(&app) {
+        for plugin in &data.plugins {
+            for skill in &plugin.skills {
+                let owners = shipped_by.entry(skill_folder_name(skill)).or_default();
+                if !owners.contains(&plugin.name) {
+                    owners.push(plugin.name.clone());
+                }
+            }
+        }
+    }
+
+    let mut groups = Vec::new();
+    for (skill_name, mut copies) in by_name {
+        let marketplace_plugins = shipped_by.remove(&skill_name).unwrap_or_default();
+        if copies.len() < 2 && marketplace_plugins.len() < 2 {
+            continue;
+        }
+
+        let mut hashes: Vec<String> = Vec::new();
+        for copy in &mut copies {
+            copy.variant = match hashes.iter().position(|h| *h == copy.content_hash) {
+                Some(i) => i,
+                None => {
+                    hashes.push(copy.content_hash.clone());
+                    hashes.len() - 1
+                }
+            };
+        }
+
+        groups.push(SkillConflictGroup {
+            resolutions: resolve(&copies),
+            skill_name,
+            copies,
+            variants: hashes.len(),
+            diverged: hashes.len() > 1,
+            marketplace_plugins,
+        });
+    }
+
+    Ok(ConflictReport {
+        skills_checked: checked,
+        diverged_count: groups.iter().filter(|g| g.diverged).count(),
+        shadowed_count: groups
+            .iter()
+            .filter(|g| g.resolutions.iter().any(|r| !r.shadowed_paths.is_empty()))
+            .count(),
+        groups,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn copy(path: &str, agent: &str, project: Option<&str>, hash: &str) -> SkillCopy {
+        SkillCopy {
+            path: path.to_string(),
+            agents: vec![agent.to_string()],
+            scope: if project.is_some() { "project" } else { "global" }.to_string(),
+            project_path: project.map(str::to_string),
+            content_hash: hash.to_string(),
+            size_bytes: 0,
+            variant: 0,
+        }
+    }
+
+    #[test]
+    fn test_project_copy_shadows_global() {
+        let copies = vec![
+            copy("/home/.claude/skills/pdf", "claude", None, "a"),
+            copy("/work/app/.claude/skills/pdf", "claude", Some("/work/app"), "b"),
+            copy("/work/app/.cursor/skills/pdf", "cursor", Some("/work/app"), "a"),
+        ];
+        let res = resolve(&copies);
+
+        let in_app = res
+            .iter()
+            .find(|r| r.agent_id == "claude" && r.project_path.as_deref() == Some("/work/app"))
+            .unwrap();
+        assert_eq!(in_app.effective_path, "/work/app/.claude/skills/pdf");
+        assert_eq!(in_app.shadowed_paths, vec!["/home/.claude/skills/pdf".to_string()]);
+
+        let cursor = res.iter().filter(|r| r.agent_id == "cursor").collect::<Vec<_>>();
+        assert_eq!(cursor.len(), 1);
+        assert!(cursor[0].shadowed_paths.is_empty() && !cursor[0].ambiguous);
+    }
+}
@@ -0,0 +1,263 @@
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::{marketplace, plugin_installer, plugin_scanner};
+
+const DEFAULT_CONTEXT_LINES: usize = 3;
+const MAX_TEXT_DIFF_BYTES: u64 = 1024 * 1024;
+
+// 单个文件的差异
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileDiff {
+    pub path: String, // relative to both skill roots
+    pub status: String, // "added" | "removed" | "modified" | "unchanged"
+    pub binary: bool,
+    pub left_size: Option<u64>,
+    pub right_size: Option<u64>,
+    pub additions: usize, // lines, text files under 1 MB only
+    pub deletions: usize,
+    pub unified: Option<String>, // unified diff for changed text files under 1 MB
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub modified: usize,
+    pub unchanged: usize,
+    pub binary_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+// 两个 skill 目录的差异
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillDiff {
+    pub left_path: String,
+    pub right_path: String,
+    pub identical: bool,
+    pub summary: DiffSummary,
+    pub files: Vec<FileDiff>, // unchanged files are left out unless asked for
+}
+
+/// Files below `root` by relative path. Install metadata is left out, it differs between every copy.
+fn list_files(root: &Path) -> Result<BTreeMap<String, PathBuf>, String> {
+    if !root.is_dir() {
+        return Err(format!("Skill directory not found: {}", root.display()));
+    }
+    let mut files = BTreeMap::new();
+    let walker = WalkBuilder::new(root)
+        .standard_filters(false)
+        .follow_links(true)
+        .filter_entry(|e| e.file_name() != ".git")
+        .build();
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(root).unwrap_or(entry.path()).to_string_lossy().replace('\\', "/");
+        if rel != ".metadata.json" {
+            files.insert(rel, entry.into_path());
+        }
+    }
+    Ok(files)
+}
+
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(8192)].contains(&0) || std::str::from_utf8(bytes).is_err()
+}
+
+fn diff_file(rel: &str, left: Option<&Path>, right: Option<&Path>, context: usize) -> Result<FileDiff, String> {
+    let read = |p: Option<&Path>| -> Result<Option<Vec<u8>>, String> {
+        p.map(|p| fs::read(p).map_err(|e| format!("Failed to read {}: {}", p.display(), e))).transpose()
+    };
+    let (left_bytes, right_bytes) = (read(left)?, read(right)?);
+    let binary = left_bytes.as_deref().is_some_and(is_binary) || right_bytes.as_deref().is_some_and(is_binary);
+
+    let status = match (&left_bytes, &right_bytes) {
+        (None, Some(_)) => "added",
+        (Some(_), None) => "removed",
+        (Some(l), Some(r)) if l == r => "unchanged",
+        _ => "modified",
+    };
+    let mut diff = FileDiff {
+        path: rel.to_string(),
+        status: status.to_string(),
+        binary,
+        left_size: left_bytes.as_ref().map(|b| b.len() as u64),
+        right_size: right_bytes.as_ref().map(|b| b.len() as u64),
+        additions: 0,
+        deletions: 0,
+        unified: None,
+    };
+    if binary || status == "unchanged" {
+        return Ok(diff);
+    }
+    // Line diffs of very large files are slow and unreadable: report them as modified only
+    if diff.left_size.max(diff.right_size).unwrap_or(0) > MAX_TEXT_DIFF_BYTES {
+        return Ok(diff);
+    }
+
+    let old = String::from_utf8(left_bytes.unwrap_or_default()).unwrap_or_default();
+    let new = String::from_utf8(right_bytes.unwrap_or_default()).unwrap_or_default();
+    let text_diff = TextDiff::from_lines(&old, &new);
+    for change in text_diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => diff.additions += 1,
+            ChangeTag::Delete => diff.deletions += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    let old_header = if status == "added" { "/dev/null".to_string() } else { format!("a/{}", rel) };
+    let new_header = if status == "removed" { "/dev/null".to_string() } else { format!("b/{}", rel) };
+    diff.unified = Some(
+        text_diff
+            .unified_diff()
+            .context_radius(context)
+            .header(&old_header, &new_header)
+            .to_string(),
+    );
+    Ok(diff)
+}
+
+fn diff_dirs(left: &Path, right: &Path, context: usize, include_unchanged: bool) -> Result<SkillDiff, String> {
+    let left_files = list_files(left)?;
+    let right_files = list_files(right)?;
+
+    let mut paths: Vec<&String> = left_files.keys().chain(right_files.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut summary = DiffSummary::default();
+    let mut files = Vec::new();
+    for rel in paths {
+        let diff = diff_file(
+            rel,
+            left_files.get(rel).map(PathBuf::as_path),
+            right_files.get(rel).map(PathBuf::as_path),
+            context,
+        )?;
+        match diff.status.as_str() {
+            "added" => summary.added += 1,
+            "removed" => summary.removed += 1,
+            "modified" => summary.modified += 1,
+            _ => summary.unchanged += 1,
+        }
+        if diff.binary && diff.status != "unchanged" {
+            summary.binary_changed += 1;
+        }
+        summary.additions += diff.additions;
+        summary.deletions += diff.deletions;
+        if include_unchanged || diff.status != "unchanged" {
+            files.push(diff);
+        }
+    }
+
+    Ok(SkillDiff {
+        left_path: left.to_string_lossy().to_string(),
+        right_path: right.to_string_lossy().to_string(),
+        identical: summary.added + summary.removed + summary.modified == 0,
+        summary,
+        files,
+    })
+}
+
+/// Marketplace folder an installed skill was copied from: by install provenance, else by folder name
+fn source_of_installed(app: &tauri::AppHandle, installed: &Path) -> Result<PathBuf, String> {
+    let folder = installed.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let provenance = plugin_scanner::read_skill_metadata(installed).plugin_name;
+    let data = marketplace::get_marketplace_data_cached(app)?;
+
+    let ships_folder = |skill: &String| Path::new(skill.trim_start_matches("./")).file_name().is_some_and(|n| *n == *folder);
+    let plugin = data
+        .plugins
+        .iter()
+        .filter(|p| p.skills.iter().any(ships_folder))
+        .find(|p| provenance.as_ref().is_none_or(|name| *name == p.name))
+        .ok_or_else(|| format!("No marketplace source found for skill '{}'", folder))?;
+
+    let root = plugin_installer::resolve_source_root(app, plugin)?;
+    let skill = plugin.skills.iter().find(|s| ships_folder(s)).ok_or("Skill not in plugin")?;
+    Ok(root.join(skill.trim_start_matches("./")))
+}
+
+/// 比较两个 skill 目录（全局 vs 项目、代理 A vs 代理 B 等）
+#[tauri::command]
+pub fn diff_skill_dirs(
+    left_path: String,
+    right_path: String,
+    context_lines: Option<usize>,
+    include_unchanged: Option<bool>,
+) -> Result<SkillDiff, String> {
+    diff_dirs(
+        Path::new(&left_path),
+        Path::new(&right_path),
+        context_lines.unwrap_or(DEFAULT_CONTEXT_LINES),
+        include_unchanged.unwrap_or(false),
+    )
+}
+
+/// 比较已安装的 skill 与其市场来源（左侧为来源，右侧为已安装副本）
+#[tauri::command]
+pub fn diff_skill_with_source(
+    app: tauri::AppHandle,
+    installed_path: String,
+    context_lines: Option<usize>,
+) -> Result<SkillDiff, String> {
+    let installed = Path::new(&installed_path);
+    let source = source_of_installed(&app, installed)?;
+    diff_dirs(&source, installed, context_lines.unwrap_or(DEFAULT_CONTEXT_LINES), false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_and_binary_changes() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let base = tmp.path();
+        let (left, right) = (base.join("left"), base.join("right"));
+        for dir in [&left, &right] {
+            fs::create_dir_all(dir.join("assets")).unwrap();
+            fs::write(dir.join("README.md"), "same\n").unwrap();
+        }
+        fs::write(left.join("SKILL.md"), "---\nname: a\n---\none\ntwo\n").unwrap();
+        fs::write(right.join("SKILL.md"), "---\nname: a\n---\none\n2\n").unwrap();
+        fs::write(left.join("assets/logo.png"), [0u8, 1, 2]).unwrap();
+        fs::write(right.join("assets/logo.png"), [0u8, 1, 3]).unwrap();
+        fs::write(right.join("notes.md"), "new\n").unwrap();
+        fs::write(right.join(".metadata.json"), "{}").unwrap();
+
+        let diff = diff_dirs(&left, &right, 3, false).unwrap();
+        assert!(!diff.identical);
+        assert_eq!((diff.summary.added, diff.summary.modified, diff.summary.unchanged), (1, 2, 1));
+        assert_eq!(diff.summary.binary_changed, 1);
+
+        let skill = diff.files.iter().find(|f| f.path == "SKILL.md").unwrap();
+        assert_eq!((skill.additions, skill.deletions), (1, 1));
+        assert!(skill.unified.as_ref().unwrap().contains("-two\n+2\n"));
+        let logo = diff.files.iter().find(|f| f.path == "assets/logo.png").unwrap();
+        assert!(logo.binary && logo.unified.is_none());
+
+        assert!(diff_dirs(&left, &left, 3, false).unwrap().identical);
+    }
+
+    #[test]
+    fn test_large_files_skip_the_line_diff() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (left, right) = (tmp.path().join("a.txt"), tmp.path().join("b.txt"));
+        let big = "line\n".repeat(MAX_TEXT_DIFF_BYTES as usize / 5 + 1);
+        fs::write(&left, &big).unwrap();
+        fs::write(&right, format!("{}more\n", big)).unwrap();
+
+        let diff = diff_file("a.txt", Some(&left), Some(&right), 3).unwrap();
+        assert_eq!(diff.status, "modified");
+        assert_eq!((diff.additions, diff.deletions), (0, 0));
+        assert!(diff.unified.is_none());
+    }
+}
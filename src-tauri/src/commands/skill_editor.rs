@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::commands::{frontmatter, skill_lint, symlinks};
+
+const MAX_EDITABLE_BYTES: u64 = 2 * 1024 * 1024;
+const MAX_TREE_DEPTH: usize = 8;
+
+// skill 文件树节点
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillFileNode {
+    pub name: String,
+    pub path: String, // relative to the skill root, '/' separated
+    pub is_dir: bool,
+    pub is_symlink: bool, // linked folders are listed but not expanded
+    pub size_bytes: u64,
+    pub children: Vec<SkillFileNode>,
+}
+
+// 读取的文件内容
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillFileContent {
+    pub path: String,
+    pub content: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<String>,
+    pub can_undo: bool,
+}
+
+// 写入结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillFileWriteResult {
+    pub path: String,
+    pub size_bytes: u64,
+    pub can_undo: bool,
+    pub lint: Option<skill_lint::LintReport>, // after saving SKILL.md
+}
+
+// 备份记录，与备份内容放在一起
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct BackupMeta {
+    path: String, // absolute path of the edited file
+    existed: bool, // false: the edit created the file, undo deletes it
+    saved_at: String,
+}
+
+fn get_backups_dir() -> Result<PathBuf, String> {
+    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
+    let dir = home_dir.join(".skillbox-studio").join("edit-backups");
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
+
+/// Canonical skill root: an existing folder with a SKILL.md, so arbitrary folders can't be edited
+fn skill_root(skill_path: &str) -> Result<PathBuf, String> {
+    let root = fs::canonicalize(skill_path).map_err(|e| format!("Skill directory not found: {}", e))?;
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", skill_path));
+    }
+    let has_skill_md = fs::read_dir(&root)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .any(|e| is_skill_md(&root, &e.path()));
+    if !has_skill_md {
+        return Err(format!("{} is not a skill (no SKILL.md)", skill_path));
+    }
+    Ok(root)
+}
+
+/// `path` is the root's SKILL.md, in any letter case (macOS and Windows file names ignore it)
+fn is_skill_md(root: &Path, path: &Path) -> bool {
+    path.parent() == Some(root)
+        && path.file_name().is_some_and(|n| n.to_string_lossy().eq_ignore_ascii_case("SKILL.md"))
+        && !path.is_dir()
+}
+
+/// Resolve `rel` inside `root`. Absolute paths, `..` and links leading outside the root are refused.
+fn resolve_inside(root: &Path, rel: &str) -> Result<PathBuf, String> {
+    let rel_path = Path::new(rel);
+    if rel.trim().is_empty() || rel_path.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(format!("Invalid path '{}': must be relative to the skill folder", rel));
+    }
+    let path = root.join(rel_path);
+
+    // The deepest existing ancestor decides where the path really lands
+    let mut existing = path.as_path();
+    while !existing.exists() && fs::symlink_metadata(existing).is_err() {
+        existing = existing.parent().ok_or("Invalid path")?;
+    }
+    let real = fs::canonicalize(existing).map_err(|_| format!("Path '{}' points outside the skill folder", rel))?;
+    if !real.starts_with(root) {
+        return Err(format!("Path '{}' points outside the skill folder", rel));
+    }
+    Ok(path)
+}
+
+fn rel_string(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+fn build_tree(root: &Path, dir: &Path, depth: usize) -> Vec<SkillFileNode> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut nodes: Vec<SkillFileNode> = entries
+        .flatten()
+        .filter(|e| e.file_name() != ".git")
+        .map(|entry| {
+            let path = entry.path();
+            let is_symlink = symlinks::is_symlink(&path);
+            let is_dir = path.is_dir();
+            let children = if is_dir && !is_symlink && depth < MAX_TREE_DEPTH {
+                build_tree(root, &path, depth + 1)
+            } else {
+                Vec::new()
+            };
+            SkillFileNode {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: rel_string(root, &path),
+                is_dir,
+                is_symlink,
+                size_bytes: if is_dir { 0 } else { fs::metadata(&path).map(|m| m.len()).unwrap_or(0) },
+                children,
+            }
+        })
+        .collect();
+    // Folders first, then by name
+    nodes.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    nodes
+}
+
+fn backup_paths(backups: &Path, file: &Path) -> (PathBuf, PathBuf) {
+    let key = format!("{:x}", Sha256::digest(file.to_string_lossy().as_bytes()));
+    (backups.join(format!("{}.bak", key)), backups.join(format!("{}.json", key)))
+}
+
+fn has_backup(backups: &Path, file: &Path) -> bool {
+    backup_paths(backups, file).1.is_file()
+}
+
+fn read_file(root: &Path, rel: &str, backups: &Path) -> Result<SkillFileContent, String> {
+    let path = resolve_inside(root, rel)?;
+    let meta = fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", rel, e))?;
+    if meta.is_dir() {
+        return Err(format!("{} is a directory", rel));
+    }
+    if meta.len() > MAX_EDITABLE_BYTES {
+        return Err(format!("{} is too large to edit ({} bytes)", rel, meta.len()));
+    }
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", rel, e))?;
+    let content = match String::from_utf8(bytes) {
+        Ok(text) if !text.contains('\0') => text,
+        _ => return Err(format!("{} is a binary file", rel)),
+    };
+
+    Ok(SkillFileContent {
+        path: rel_string(root, &path),
+        content,
+        size_bytes: meta.len(),
+        modified_at: meta.modified().ok().map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+        can_undo: has_backup(backups, &path),
+    })
+}
+
+/// SKILL.md must keep a frontmatter agents can load
+fn validate_skill_md(content: &str) -> Result<(), String> {
+    let parsed = frontmatter::parse(content).map_err(|e| format!("SKILL.md not saved: {}", e))?;
+    if parsed.frontmatter.is_none() {
+        return Err("SKILL.md not saved: it must start with YAML frontmatter".to_string());
+    }
+    for (key, value) in [("name", parsed.name()), ("description", parsed.description())] {
+        if value.is_none_or(|v| v.trim().is_empty()) {
+            return Err(format!("SKILL.md not saved: frontmatter needs a non-empty '{}'", key));
+        }
+    }
+    Ok(())
+}
+
+fn write_file(root: &Path, rel: &str, content: &str, backups: &Path) -> Result<PathBuf, String> {
+    let path = resolve_inside(root, rel)?;
+    if path.is_dir() {
+        return Err(format!("{} is a directory", rel));
+    }
+    if is_skill_md(root, &path) {
+        validate_skill_md(content)?;
+    }
+
+    // Keep what was there before, so this edit can be undone
+    let existed = path.exists();
+    let (bak, meta) = backup_paths(backups, &path);
+    if existed {
+        fs::copy(&path, &bak).map_err(|e| format!("Failed to back up {}: {}", rel, e))?;
+    } else {
+        let _ = fs::remove_file(&bak);
+    }
+    let record = BackupMeta {
+        path: path.to_string_lossy().to_string(),
+        existed,
+        saved_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let record = serde_json::to_string_pretty(&record).map_err(|e| e.to_string())?;
+    fs::write(&meta, record).map_err(|e| format!("Failed to back up {}: {}", rel, e))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    // Write next to the target and rename, so a failed write never leaves half a file
+    let tmp = path.with_extension("skillbox-tmp");
+    fs::write(&tmp, content).map_err(|e| format!("Failed to write {}: {}", rel, e))?;
+    if existed {
+        if let Ok(m) = fs::metadata(&path) {
+            let _ = fs::set_permissions(&tmp, m.permissions());
+        }
+    }
+    fs::rename(&tmp, &path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("Failed to write {}: {}", rel, e)
+    })?;
+    Ok(path)
+}
+
+fn undo_edit(root: &Path, rel: &str, backups: &Path) -> Result<Option<String>, String> {
+    let path = resolve_inside(root, rel)?;
+    let (bak, meta) = backup_paths(backups, &path);
+    let record: BackupMeta = fs::read_to_string(&meta)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .ok_or_else(|| format!("Nothing to undo for {}", rel))?;
+
+    let restored = if record.existed {
+        fs::copy(&bak, &path).map_err(|e| format!("Failed to restore {}: {}", rel, e))?;
+        Some(fs::read_to_string(&path).map_err(|e| e.to_string())?)
+    } else {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", rel, e))?;
+        }
+        None
+    };
+    let _ = fs::remove_file(&bak);
+    let _ = fs::remove_file(&meta);
+    Ok(restored)
+}
+
+/// 列出 skill 目录的文件树
+#[tauri::command]
+pub fn list_skill_files(skill_path: String) -> Result<Vec<SkillFileNode>, String> {
+    let root = skill_root(&skill_path)?;
+    Ok(build_tree(&root, &root, 0))
+}
+
+/// 读取 skill 内的文本文件
+#[tauri::command]
+pub fn read_skill_file(skill_path: String, file_path: String) -> Result<SkillFileContent, String> {
+    let root = skill_root(&skill_path)?;
+    read_file(&root, &file_path, &get_backups_dir()?)
+}
+
+/// 写入 skill 内的文件；保存 SKILL.md 前校验 frontmatter，并保留上一版本用于撤销
+#[tauri::command]
+pub fn write_skill_file(skill_path: String, file_path: String, content: String) -> Result<SkillFileWriteResult, String> {
+    let root = skill_root(&skill_path)?;
+    let path = write_file(&root, &file_path, &content, &get_backups_dir()?)?;
+    let lint = if is_skill_md(&root, &path) {
+        skill_lint::lint_skill(root.to_string_lossy().to_string()).ok()
+    } else {
+        None
+    };
+    Ok(SkillFileWriteResult {
+        path: rel_string(&root, &path),
+        size_bytes: content.len() as u64,
+        can_undo: true,
+        lint,
+    })
+}
+
+/// 撤销对某个文件的最后一次写入。返回恢复后的内容，文件被删除时为 None
+#[tauri::command]
+pub fn undo_skill_file_edit(skill_path: String, file_path: String) -> Result<Option<String>, String> {
+    let root = skill_root(&skill_path)?;
+    undo_edit(&root, &file_path, &get_backups_dir()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (PathBuf, tempfile::TempDir) {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let base = tmp.path();
+        fs::create_dir_all(base.join("skill/references")).unwrap();
+        fs::create_dir_all(base.join("backups")).unwrap();
+        fs::write(base.join("skill/SKILL.md"), "---\nname: skill\ndescription: Test skill\n---\nBody\n").unwrap();
+        fs::write(base.join("secret.txt"), "secret").unwrap();
+        (fs::canonicalize(base.join("skill")).unwrap(), tmp)
+    }
+
+    #[test]
+    fn test_paths_stay_inside_the_skill() {
+        let (root, tmp) = setup();
+        let base = tmp.path();
+        assert!(resolve_inside(&root, "../secret.txt").is_err());
+        assert!(resolve_inside(&root, "/etc/passwd").is_err());
+        assert!(resolve_inside(&root, "references/../../secret.txt").is_err());
+        assert!(resolve_inside(&root, "references/new/api.md").is_ok());
+        assert!(skill_root(&base.to_string_lossy()).is_err());
+        assert!(skill_root(&root.to_string_lossy()).is_ok());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(base.join("secret.txt"), root.join("link.txt")).unwrap();
+            assert!(resolve_inside(&root, "link.txt").is_err());
+        }
+    }
+
+    #[test]
+    fn test_write_validates_and_undo_restores() {
+        let (root, tmp) = setup();
+        let base = tmp.path();
+        let backups = base.join("backups");
+
+        let err = write_file(&root, "SKILL.md", "---\nname: skill\n---\n", &backups).unwrap_err();
+        assert!(err.contains("description"), "{}", err);
+        assert!(write_file(&root, "skill.md", "no frontmatter", &backups).is_err());
+
+        write_file(&root, "SKILL.md", "---\nname: skill\ndescription: Changed\n---\n", &backups).unwrap();
+        assert!(read_file(&root, "SKILL.md", &backups).unwrap().can_undo);
+        let restored = undo_edit(&root, "SKILL.md", &backups).unwrap();
+        assert!(restored.unwrap().contains("Test skill"));
+
+        write_file(&root, "references/api.md", "# API\n", &backups).unwrap();
+        assert_eq!(undo_edit(&root, "references/api.md", &backups).unwrap(), None);
+        assert!(!root.join("references/api.md").exists());
+    }
+}
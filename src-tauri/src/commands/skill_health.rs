@@ -0,0 +1,234 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::commands::{frontmatter, plugin_scanner};
+
+// `references/...` or `scripts/...` paths mentioned in SKILL.md, in links, code spans or plain text
+static LOCAL_LINK_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:^|[\s(\[`"'])(?:\./)?((?:references|scripts)/[^\s)\]`"'<>,*{}]+)"#).unwrap()
+});
+
+// 健康检查发现的问题
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HealthFinding {
+    pub code: String, // "no_frontmatter" | "invalid_frontmatter" | "missing_name" | "missing_description" | "name_mismatch" | "broken_link" | ...
+    pub severity: String, // "error" | "warning"
+    pub message: String,
+    pub file: String,
+    pub line: Option<usize>, // 1-based
+}
+
+// 单个已安装 skill 的检查结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillHealth {
+    pub skill_name: String,
+    pub path: String,
+    pub agents: Vec<String>, // several agents can share one directory
+    pub scope: String, // "global" | "project"
+    pub project_path: Option<String>,
+    pub likely_ignored: bool, // agents will probably not load this skill at all
+    pub findings: Vec<HealthFinding>,
+}
+
+// 健康检查报告
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HealthReport {
+    pub skills_checked: usize,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub ignored_count: usize,
+    pub skills: Vec<SkillHealth>, // only skills with findings, most severe first
+    pub duration_ms: u64,
+}
+
+/// Check one skill folder. Returns the findings and whether agents will likely skip it.
+pub fn check_skill_dir(skill_dir: &Path) -> (Vec<HealthFinding>, bool) {
+    let skill_md = skill_dir.join("SKILL.md");
+    let file = skill_md.to_string_lossy().to_string();
+    let folder_name = skill_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match fs::read_to_string(&skill_md) {
+        Ok(content) => check_skill_md(&content, &folder_name, skill_dir, &file),
+        Err(e) => (
+            vec![HealthFinding {
+                code: "unreadable".to_string(),
+                severity: "error".to_string(),
+                message: format!("Failed to read SKILL.md: {}", e),
+                file,
+                line: None,
+            }],
+            true,
+        ),
+    }
+}
+
+fn check_skill_md(content: &str, folder_name: &str, skill_dir: &Path, file: &str) -> (Vec<HealthFinding>, bool) {
+    let mut findings = Vec::new();
+    let mut ignored = false;
+    let mut push = |code: &str, severity: &str, line: Option<usize>, message: String| {
+        findings.push(HealthFinding {
+            code: code.to_string(),
+            severity: severity.to_string(),
+            message,
+            file: file.to_string(),
+            line,
+        })
+    };
+
+    let mut body_start = 0;
+
+    match frontmatter::parse(content) {
+        Err(e) => {
+            let code = match e.kind {
+                frontmatter::FrontmatterErrorKind::Unterminated => "unterminated_frontmatter",
+                _ => "invalid_frontmatter",
+            };
+            push(code, "error", e.line.or(Some(1)), e.message);
+            ignored = true;
+        }
+        Ok(parsed) if parsed.frontmatter.is_none() => {
+            push("no_frontmatter", "error", Some(1), "SKILL.md has no YAML frontmatter".to_string());
+            ignored = true;
+        }
+        Ok(parsed) => {
+            body_start = parsed.body_line - 1;
+            let line = |key: &str| parsed.key_lines.get(key).copied();
+
+            match parsed.name().map(str::trim) {
+                None if line("name").is_none() => {
+                    push("missing_name", "error", Some(1), "Frontmatter has no 'name' field".to_string());
+                    ignored = true;
+                }
+                None | Some("") => {
+                    push("missing_name", "error", line("name"), "'name' is empty".to_string());
+                    ignored = true;
+                }
+                Some(name) if name != folder_name => push(
+                    "name_mismatch",
+                    "warning",
+                    line("name"),
+                    format!("Name '{}' does not match folder '{}'", name, folder_name),
+                ),
+                _ => {}
+            }
+
+            match parsed.description().map(str::trim) {
+                None if line("description").is_none() => {
+                    push("missing_description", "error", Some(1), "Frontmatter has no 'description' field".to_string());
+                    ignored = true;
+                }
+                None | Some("") => {
+                    push("missing_description", "error", line("description"), "'description' is empty".to_string());
+                    ignored = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for (line, target) in broken_links(content, body_start, skill_dir) {
+        push("broken_link", "warning", Some(line), format!("Linked file '{}' does not exist", target));
+    }
+
+    (findings, ignored)
+}
+
+/// `(1-based line, target)` of every `references/` or `scripts/` path in the body that doesn't exist
+pub fn broken_links(content: &str, body_start: usize, skill_dir: &Path) -> Vec<(usize, String)> {
+    let mut broken = Vec::new();
+    for (i, line) in content.lines().enumerate().skip(body_start) {
+        for cap in LOCAL_LINK_RE.captures_iter(line) {
+            let target = cap[1].split('#').next().unwrap_or_default().trim_end_matches(['.', ':', ';']);
+            if target.ends_with('/') || skill_dir.join(target).exists() {
+                continue;
+            }
+            broken.push((i + 1, target.to_string()));
+        }
+    }
+    broken
+}
+
+/// 检查所有代理、所有作用域下已安装 skill 的健康状况
+#[tauri::command]
+pub fn check_skills_health(scope: Option<String>, project_path: Option<String>) -> Result<HealthReport, String> {
+    let started = std::time::Instant::now();
+    let mut skills: Vec<SkillHealth> = Vec::new();
+    let mut checked = 0;
+
+    for (dir, agent_id, scope, project) in plugin_scanner::installed_skill_dirs(scope.as_deref(), project_path) {
+        let path = dir.to_string_lossy().to_string();
+        // Shared directories (e.g. `.agents/skills/`) are checked once and list every agent
+        if let Some(existing) = skills.iter_mut().find(|s| s.path == path) {
+            if !existing.agents.contains(&agent_id) {
+                existing.agents.push(agent_id);
+            }
+            continue;
+        }
+
+        checked += 1;
+        let (mut findings, likely_ignored) = check_skill_dir(&dir);
+        findings.sort_by_key(|f| (f.severity != "error", f.line));
+        skills.push(SkillHealth {
+            skill_name: dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            path,
+            agents: vec![agent_id],
+            scope,
+            project_path: project,
+            likely_ignored,
+            findings,
+        });
+    }
+
+    skills.retain(|s| !s.findings.is_empty());
+    let errors = |s: &SkillHealth| s.findings.iter().filter(|f| f.severity == "error").count();
+    skills.sort_by_key(|s| (!s.likely_ignored, std::cmp::Reverse(errors(s)), s.path.clone()));
+
+    let error_count = skills.iter().map(errors).sum();
+    let warning_count = skills.iter().flat_map(|s| &s.findings).filter(|f| f.severity == "warning").count();
+    Ok(HealthReport {
+        skills_checked: checked,
+        error_count,
+        warning_count,
+        ignored_count: skills.iter().filter(|s| s.likely_ignored).count(),
+        skills,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(content: &str, folder: &str) -> (Vec<(String, Option<usize>)>, bool) {
+        let (findings, ignored) = check_skill_md(content, folder, Path::new("/nonexistent/skill"), "SKILL.md");
+        (findings.into_iter().map(|f| (f.code, f.line)).collect(), ignored)
+    }
+
+    #[test]
+    fn test_missing_frontmatter_is_ignored() {
+        let (found, ignored) = codes("# Title\n", "pdf");
+        assert_eq!(found, vec![("no_frontmatter".to_string(), Some(1))]);
+        assert!(ignored);
+    }
+
+    #[test]
+    fn test_name_mismatch_and_broken_links() {
+        let content = "---\nname: pdf-tools\ndescription: >\n  Work with PDFs\n---\n# PDF\n\nSee [api](references/api.md) and run `scripts/fill.py`.\n";
+        let (found, ignored) = codes(content, "pdf");
+        assert!(!ignored);
+        assert_eq!(
+            found,
+            vec![
+                ("name_mismatch".to_string(), Some(2)),
+                ("broken_link".to_string(), Some(8)),
+                ("broken_link".to_string(), Some(8)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,534 @@
+use ignore::WalkBuilder;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::commands::{frontmatter, skill_health};
+
+const MAX_NAME_LEN: usize = 64;
+const MAX_DESCRIPTION_LEN: usize = 1024;
+const MIN_DESCRIPTION_LEN: usize = 40;
+const MAX_SKILL_MD_LINES: usize = 500;
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+// Keys from the Agent Skills spec plus the ones agents are known to read
+const KNOWN_KEYS: [&str; 11] = [
+    "name",
+    "description",
+    "license",
+    "allowed-tools",
+    "metadata",
+    "version",
+    "compatibility",
+    "model",
+    "argument-hint",
+    "disable-model-invocation",
+    "user-invocable",
+];
+
+static NAME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[a-z0-9]+(-[a-z0-9]+)*$").unwrap());
+static WHEN_TO_USE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(use (it |this( skill)? )?(when|for|to|if|whenever)|when (the user|you|asked|working|dealing)|whenever|trigger)").unwrap()
+});
+static MD_LINK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\]\(<?([^)\s>]+)>?(?:\s+[^)]*)?\)").unwrap());
+
+// 规则定义
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LintRule {
+    pub id: String,
+    pub severity: String, // "error" | "warning"
+    pub fixable: bool,
+    pub description: String,
+}
+
+const RULES: [(&str, &str, bool, &str); 14] = [
+    ("frontmatter-invalid", "error", false, "SKILL.md must start with valid YAML frontmatter"),
+    ("name-missing", "error", false, "Frontmatter needs a 'name'"),
+    ("name-format", "error", true, "Names use lowercase letters, digits and single hyphens"),
+    ("name-length", "error", true, "Names are at most 64 characters"),
+    ("name-folder-mismatch", "warning", true, "The name should match the skill folder"),
+    ("description-missing", "error", false, "Frontmatter needs a 'description'"),
+    ("description-length", "warning", false, "Descriptions are between 40 and 1024 characters"),
+    ("description-when-to-use", "warning", false, "The description should say when to use the skill"),
+    ("broken-link", "warning", false, "Relative links must point to files inside the skill"),
+    ("script-not-executable", "warning", true, "Files in scripts/ should be executable"),
+    ("script-missing-shebang", "warning", true, "Files in scripts/ should start with a '#!' line"),
+    ("file-too-large", "warning", false, "Files larger than 1 MB bloat the agent context"),
+    ("skill-md-too-long", "warning", false, "SKILL.md should stay under 500 lines"),
+    ("unknown-frontmatter-key", "warning", false, "Custom fields belong under 'metadata:'"),
+];
+
+// 一条诊断
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LintDiagnostic {
+    pub rule: String,
+    pub severity: String, // "error" | "warning"
+    pub message: String,
+    pub file: String, // relative to the skill folder
+    pub line: Option<usize>, // 1-based
+    pub fixable: bool,
+}
+
+// 单个 skill 的检查结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LintReport {
+    pub skill_path: String,
+    pub diagnostics: Vec<LintDiagnostic>,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub fixable_count: usize,
+}
+
+// 自动修复结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LintFixResult {
+    pub applied: Vec<LintDiagnostic>,
+    pub failed: Vec<String>,
+    pub report: LintReport, // after the fixes
+}
+
+fn diagnostic(rule: &str, message: String, file: &str, line: Option<usize>, fixable: bool) -> LintDiagnostic {
+    let severity = RULES.iter().find(|r| r.0 == rule).map_or("warning", |r| r.1);
+    LintDiagnostic {
+        rule: rule.to_string(),
+        severity: severity.to_string(),
+        message,
+        file: file.to_string(),
+        line,
+        fixable,
+    }
+}
+
+/// Lowercase, hyphen-separated and at most 64 characters: "My PDF_Tools" -> "my-pdf-tools"
+pub fn normalize_name(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.truncate(MAX_NAME_LEN);
+    out.trim_end_matches('-').to_string()
+}
+
+/// The `name:` value sits on its own line and can be rewritten in place
+fn name_is_inline(content: &str, line: usize) -> bool {
+    content
+        .lines()
+        .nth(line - 1)
+        .and_then(|l| l.split_once(':'))
+        .map(|(_, v)| v.trim())
+        .is_some_and(|v| !v.is_empty() && !v.starts_with(['>', '|']))
+}
+
+fn lint_skill_md(content: &str, folder_name: &str, skill_dir: &Path) -> Vec<LintDiagnostic> {
+    let mut out = Vec::new();
+    let file = "SKILL.md";
+
+    let line_count = content.lines().count();
+    if line_count > MAX_SKILL_MD_LINES {
+        out.push(diagnostic(
+            "skill-md-too-long",
+            format!("SKILL.md has {} lines, move details into references/", line_count),
+            file,
+            None,
+            false,
+        ));
+    }
+
+    let parsed = match frontmatter::parse(content) {
+        Ok(p) if p.frontmatter.is_some() => p,
+        Ok(_) => {
+            out.push(diagnostic("frontmatter-invalid", "SKILL.md has no YAML frontmatter".to_string(), file, Some(1), false));
+            return out;
+        }
+        Err(e) => {
+            out.push(diagnostic("frontmatter-invalid", e.message, file, e.line, false));
+            return out;
+        }
+    };
+    let line = |key: &str| parsed.key_lines.get(key).copied();
+
+    match parsed.name().map(str::trim).filter(|n| !n.is_empty()) {
+        None => out.push(diagnostic("name-missing", "Frontmatter has no 'name'".to_string(), file, line("name").or(Some(1)), false)),
+        Some(name) => {
+            let inline = line("name").is_some_and(|l| name_is_inline(content, l));
+            if !NAME_RE.is_match(name) {
+                out.push(diagnostic(
+                    "name-format",
+                    format!("Name '{}' should be '{}'", name, normalize_name(name)),
+                    file,
+                    line("name"),
+                    inline && !normalize_name(name).is_empty(),
+                ));
+            }
+            if name.chars().count() > MAX_NAME_LEN {
+                out.push(diagnostic(
+                    "name-length",
+                    format!("Name is {} characters, the limit is {}", name.chars().count(), MAX_NAME_LEN),
+                    file,
+                    line("name"),
+                    inline,
+                ));
+            }
+            if normalize_name(name) != folder_name {
+                out.push(diagnostic(
+                    "name-folder-mismatch",
+                    format!("Name '{}' does not match folder '{}'", name, folder_name),
+                    file,
+                    line("name"),
+                    inline && NAME_RE.is_match(folder_name) && folder_name.len() <= MAX_NAME_LEN,
+                ));
+            }
+        }
+    }
+
+    match parsed.description().map(str::trim).filter(|d| !d.is_empty()) {
+        None => out.push(diagnostic(
+            "description-missing",
+            "Frontmatter has no 'description'".to_string(),
+            file,
+            line("description").or(Some(1)),
+            false,
+        )),
+        Some(desc) => {
+            let len = desc.chars().count();
+            if len > MAX_DESCRIPTION_LEN {
+                out.push(diagnostic(
+                    "description-length",
+                    format!("Description is {} characters, the limit is {}", len, MAX_DESCRIPTION_LEN),
+                    file,
+                    line("description"),
+                    false,
+                ));
+            } else if len < MIN_DESCRIPTION_LEN {
+                out.push(diagnostic(
+                    "description-length",
+                    format!("Description is only {} characters, agents pick skills by it", len),
+                    file,
+                    line("description"),
+                    false,
+                ));
+            }
+            if !WHEN_TO_USE_RE.is_match(desc) {
+                out.push(diagnostic(
+                    "description-when-to-use",
+                    "Description doesn't say when to use the skill (e.g. \"Use when ...\")".to_string(),
+                    file,
+                    line("description"),
+                    false,
+                ));
+            }
+        }
+    }
+
+    for key in parsed.keys.iter().filter(|k| !KNOWN_KEYS.contains(&k.as_str())) {
+        out.push(diagnostic(
+            "unknown-frontmatter-key",
+            format!("Unknown key '{}', move it under 'metadata:'", key),
+            file,
+            line(key),
+            false,
+        ));
+    }
+
+    // references/ and scripts/ mentions, plus any other relative markdown link
+    let body_start = parsed.body_line - 1;
+    let mut broken = skill_health::broken_links(content, body_start, skill_dir);
+    for (i, text) in content.lines().enumerate().skip(body_start) {
+        for cap in MD_LINK_RE.captures_iter(text) {
+            let target = cap[1].split('#').next().unwrap_or_default();
+            if target.is_empty() || target.contains("://") || target.starts_with(['/', '~']) || target.starts_with("mailto:") {
+                continue;
+            }
+            let target = target.trim_start_matches("./");
+            if !skill_dir.join(target).exists() && !broken.iter().any(|(l, t)| *l == i + 1 && t == target) {
+                broken.push((i + 1, target.to_string()));
+            }
+        }
+    }
+    broken.sort();
+    for (l, target) in broken {
+        out.push(diagnostic("broken-link", format!("Linked file '{}' does not exist", target), file, Some(l), false));
+    }
+
+    out
+}
+
+/// Interpreter line for a script extension, when we can tell
+fn shebang_for(path: &Path) -> Option<&'static str> {
+    match path.extension()?.to_str()? {
+        "sh" => Some("#!/usr/bin/env bash"),
+        "py" => Some("#!/usr/bin/env python3"),
+        "js" | "mjs" => Some("#!/usr/bin/env node"),
+        "rb" => Some("#!/usr/bin/env ruby"),
+        "pl" => Some("#!/usr/bin/env perl"),
+        _ => None,
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_meta: &fs::Metadata) -> bool {
+    true // no execute bit to check
+}
+
+fn lint_files(skill_dir: &Path) -> Vec<LintDiagnostic> {
+    let mut out = Vec::new();
+    let walker = WalkBuilder::new(skill_dir)
+        .standard_filters(false)
+        .follow_links(false)
+        .filter_entry(|e| e.file_name() != ".git")
+        .build();
+
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        let rel = path.strip_prefix(skill_dir).unwrap_or(path).to_string_lossy().replace('\\', "/");
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+
+        if meta.len() > MAX_FILE_BYTES {
+            out.push(diagnostic(
+                "file-too-large",
+                format!("{} is {:.1} MB", rel, meta.len() as f64 / (1024.0 * 1024.0)),
+                &rel,
+                None,
+                false,
+            ));
+        }
+
+        if !rel.starts_with("scripts/") || meta.len() > MAX_FILE_BYTES {
+            continue;
+        }
+        // Only text files can carry a shebang; a binary in scripts/ is left alone
+        let Ok(bytes) = fs::read(path) else {
+            continue;
+        };
+        if bytes.contains(&0) {
+            continue;
+        }
+        if !is_executable(&meta) {
+            out.push(diagnostic("script-not-executable", format!("{} is not executable", rel), &rel, None, true));
+        }
+        if !bytes.starts_with(b"#!") && !bytes.is_empty() {
+            let fixable = shebang_for(path).is_some();
+            out.push(diagnostic("script-missing-shebang", format!("{} has no '#!' line", rel), &rel, Some(1), fixable));
+        }
+    }
+    out
+}
+
+fn lint_dir(skill_dir: &Path) -> Result<LintReport, String> {
+    if !skill_dir.is_dir() {
+        return Err(format!("Skill directory not found: {}", skill_dir.display()));
+    }
+    let folder_name = skill_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+    let mut diagnostics = match fs::read_to_string(skill_dir.join("SKILL.md")) {
+        Ok(content) => lint_skill_md(&content, &folder_name, skill_dir),
+        Err(e) => vec![diagnostic("frontmatter-invalid", format!("Failed to read SKILL.md: {}", e), "SKILL.md", None, false)],
+    };
+    diagnostics.extend(lint_files(skill_dir));
+    diagnostics.sort_by(|a, b| (a.severity != "error", &a.file, a.line).cmp(&(b.severity != "error", &b.file, b.line)));
+
+    Ok(LintReport {
+        skill_path: skill_dir.to_string_lossy().to_string(),
+        error_count: diagnostics.iter().filter(|d| d.severity == "error").count(),
+        warning_count: diagnostics.iter().filter(|d| d.severity == "warning").count(),
+        fixable_count: diagnostics.iter().filter(|d| d.fixable).count(),
+        diagnostics,
+    })
+}
+
+/// Rewrite the `name:` line of SKILL.md
+fn set_name(skill_dir: &Path, line: usize, name: &str) -> Result<(), String> {
+    let path = skill_dir.join("SKILL.md");
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let rewritten: Vec<String> = content
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(i, l)| {
+            if i + 1 != line {
+                return l.to_string();
+            }
+            let ending = &l[l.trim_end_matches(['\r', '\n']).len()..];
+            format!("name: {}{}", name, ending)
+        })
+        .collect();
+    fs::write(&path, rewritten.concat()).map_err(|e| e.to_string())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+    // Execute wherever read is allowed, like `chmod +x` with a normal umask
+    perms.set_mode(perms.mode() | ((perms.mode() & 0o444) >> 2));
+    fs::set_permissions(path, perms).map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// The one name that satisfies every selected name rule. The folder name wins when it is
+/// itself a valid name; otherwise the current name is normalized.
+fn target_name(current: &str, folder: &str, rules: &[&str]) -> String {
+    let folder_is_valid = NAME_RE.is_match(folder) && folder.len() <= MAX_NAME_LEN;
+    let reformat = rules.iter().any(|r| matches!(*r, "name-format" | "name-length"));
+    if rules.contains(&"name-folder-mismatch") && (folder_is_valid || !reformat) {
+        folder.to_string()
+    } else {
+        normalize_name(current)
+    }
+}
+
+/// All name diagnostics rewrite the same line, so they are fixed together in one write
+fn fix_name(skill_dir: &Path, diagnostics: &[LintDiagnostic]) -> Result<(), String> {
+    let line = diagnostics.iter().find_map(|d| d.line).ok_or("No line for 'name'")?;
+    let current = frontmatter::parse_file(&skill_dir.join("SKILL.md")).map_err(|e| e.to_string())?;
+    let current = current.name().unwrap_or_default().to_string();
+    let folder = skill_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let rules: Vec<&str> = diagnostics.iter().map(|d| d.rule.as_str()).collect();
+    let name = target_name(&current, &folder, &rules);
+    if name != current {
+        set_name(skill_dir, line, &name)?;
+    }
+    Ok(())
+}
+
+fn apply_fix(skill_dir: &Path, d: &LintDiagnostic) -> Result<(), String> {
+    let path = skill_dir.join(&d.file);
+    match d.rule.as_str() {
+        "script-not-executable" => make_executable(&path),
+        "script-missing-shebang" => {
+            let shebang = shebang_for(&path).ok_or("Unknown script type")?;
+            let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            if content.starts_with("#!") {
+                return Ok(());
+            }
+            fs::write(&path, format!("{}\n{}", shebang, content)).map_err(|e| e.to_string())
+        }
+        other => Err(format!("Rule '{}' has no autofix", other)),
+    }
+}
+
+/// 列出所有检查规则
+#[tauri::command]
+pub fn list_lint_rules() -> Vec<LintRule> {
+    RULES
+        .iter()
+        .map(|(id, severity, fixable, description)| LintRule {
+            id: id.to_string(),
+            severity: severity.to_string(),
+            fixable: *fixable,
+            description: description.to_string(),
+        })
+        .collect()
+}
+
+/// 检查一个 skill 目录
+#[tauri::command]
+pub fn lint_skill(skill_path: String) -> Result<LintReport, String> {
+    lint_dir(Path::new(&skill_path))
+}
+
+/// 应用可自动修复的诊断；`rules` 为空时修复全部
+#[tauri::command]
+pub fn fix_skill_lint(skill_path: String, rules: Option<Vec<String>>) -> Result<LintFixResult, String> {
+    let skill_dir = Path::new(&skill_path);
+    let before = lint_dir(skill_dir)?;
+
+    let mut applied = Vec::new();
+    let mut failed = Vec::new();
+    let (names, others): (Vec<_>, Vec<_>) = before
+        .diagnostics
+        .into_iter()
+        .filter(|d| d.fixable && rules.as_ref().is_none_or(|r| r.contains(&d.rule)))
+        .partition(|d| d.rule.starts_with("name-"));
+    if !names.is_empty() {
+        match fix_name(skill_dir, &names) {
+            Ok(()) => applied.extend(names),
+            Err(e) => failed.push(format!("name (SKILL.md): {}", e)),
+        }
+    }
+    for d in others {
+        match apply_fix(skill_dir, &d) {
+            Ok(()) => applied.push(d),
+            Err(e) => failed.push(format!("{} ({}): {}", d.rule, d.file, e)),
+        }
+    }
+
+    Ok(LintFixResult { applied, failed, report: lint_dir(skill_dir)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(content: &str, folder: &str) -> Vec<String> {
+        lint_skill_md(content, folder, Path::new("/nonexistent/skill")).into_iter().map(|d| d.rule).collect()
+    }
+
+    #[test]
+    fn test_name_and_description_rules() {
+        assert_eq!(normalize_name(" My PDF__Tools! "), "my-pdf-tools");
+
+        let content = "---\nname: PDF Tools\ndescription: PDFs\nauthor: me\n---\nSee [guide](docs/guide.md) and https://example.com.\n";
+        assert_eq!(
+            rules(content, "pdf-tools"),
+            vec![
+                "name-format",
+                "description-length",
+                "description-when-to-use",
+                "unknown-frontmatter-key",
+                "broken-link",
+            ]
+        );
+
+        let content = "---\nname: pdf-tools\ndescription: Fill and merge PDF forms. Use when the user asks about PDF files.\n---\n# PDF\n";
+        assert!(rules(content, "pdf-tools").is_empty());
+    }
+
+    #[test]
+    fn test_name_fixes_agree_on_one_name() {
+        let both = ["name-format", "name-folder-mismatch"];
+        assert_eq!(target_name("PDF Toolkit", "pdf-tools", &both), "pdf-tools");
+        assert_eq!(target_name("PDF Toolkit", "pdf-tools", &["name-format"]), "pdf-toolkit");
+        // An invalid folder name is never copied into the frontmatter
+        assert_eq!(target_name("PDF Toolkit", "PDF_Tools", &both), "pdf-toolkit");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fixes_name_and_scripts() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("pdf-tools");
+        fs::create_dir_all(dir.join("scripts")).unwrap();
+        fs::write(
+            dir.join("SKILL.md"),
+            "---\nname: PDF Tools\ndescription: Fill and merge PDF forms. Use when the user asks about PDF files.\n---\n# PDF\n",
+        )
+        .unwrap();
+        fs::write(dir.join("scripts/fill.py"), "print('hi')\n").unwrap();
+
+        let result = fix_skill_lint(dir.to_string_lossy().to_string(), None).unwrap();
+        assert_eq!(result.applied.len(), 3, "{:?}", result.applied);
+        assert!(result.report.diagnostics.is_empty(), "{:?}", result.report.diagnostics);
+        assert!(fs::read_to_string(dir.join("scripts/fill.py")).unwrap().starts_with("#!/usr/bin/env python3\n"));
+    }
+}
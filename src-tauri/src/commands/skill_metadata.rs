@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use tauri::{AppHandle, Manager};
 
+use crate::commands::frontmatter;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SkillMetadata {
     pub name: String,
@@ -35,51 +37,11 @@ pub struct ExportedSkill {
 
 /// Parse SKILL.md frontmatter to extract name and description
 fn parse_skill_metadata(content: &str) -> Option<SkillMetadata> {
-    // SKILL.md format:
-    // ---
-    // name: Skill Name
-    // description: Skill description here
-    // license: ...
-    // ---
-
-    let lines: Vec<&str> = content.lines().collect();
-
-    if lines.is_empty() {
-        return None;
-    }
-
-    // Allow for some whitespace
-    if lines[0].trim() != "---" {
-        return None;
-    }
-
-    let mut name: Option<String> = None;
-    let mut description: Option<String> = None;
-
-    // Parse frontmatter
-    for i in 1..lines.len() {
-        let line = lines[i].trim();
-
-        if line == "---" {
-            // End of frontmatter
-            break;
-        }
-
-        if let Some(value) = line.strip_prefix("name:") {
-            name = Some(value.trim().to_string());
-        } else if let Some(value) = line.strip_prefix("description:") {
-            description = Some(value.trim().to_string());
-        }
-    }
-
-    if let (Some(n), Some(d)) = (name, description) {
-        Some(SkillMetadata {
-            name: n,
-            description: d,
-        })
-    } else {
-        None
-    }
+    let parsed = frontmatter::parse(content).ok()?;
+    Some(SkillMetadata {
+        name: parsed.name()?.to_string(),
+        description: parsed.description()?.trim().to_string(),
+    })
 }
 
 /// Helper to find Skill-Box root dynamically
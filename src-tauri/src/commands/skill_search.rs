@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::{frontmatter, local_skills_registry, local_skills_scanner, marketplace, plugin_installer, plugin_scanner, scan_index};
+
+// Relative importance of each searchable field
+const NAME_WEIGHT: f64 = 5.0;
+const CATEGORY_WEIGHT: f64 = 2.0;
+const DESCRIPTION_WEIGHT: f64 = 2.0;
+const AUTHOR_WEIGHT: f64 = 1.0;
+const BODY_WEIGHT: f64 = 1.0;
+
+const DEFAULT_LIMIT: usize = 50;
+
+/// [start, end) char ranges of a match
+type Ranges = Vec<(usize, usize)>;
+
+// 某个字段内的命中位置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldMatch {
+    pub field: String, // "name" | "category" | "description" | "author" | "body"
+    pub text: String, // the field value, or the matching line for "body"
+    pub ranges: Ranges, // [start, end) in chars of `text`
+}
+
+// 搜索结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchHit {
+    pub origin: String, // "installed" | "marketplace" | "local"
+    pub name: String,
+    pub description: Option<String>,
+    pub plugin_name: Option<String>,
+    pub path: Option<String>,
+    pub agents: Vec<String>, // installed hits only
+    pub score: f64,
+    pub matches: Vec<FieldMatch>,
+}
+
+/// One searchable document before scoring
+struct Candidate {
+    origin: &'static str,
+    name: String,
+    description: Option<String>,
+    category: Option<String>,
+    author: Option<String>,
+    plugin_name: Option<String>,
+    path: Option<String>,
+    agents: Vec<String>,
+    bodies: Bodies,
+}
+
+/// Where a candidate's SKILL.md bodies are
+enum Bodies {
+    Files(Vec<PathBuf>),
+    // A marketplace plugin's skills; its source root is only resolved once a body is searched
+    Plugin(Box<marketplace::Plugin>),
+}
+
+/// Resolves a marketplace plugin's source root
+type RootLookup<'a> = dyn FnMut(&marketplace::Plugin) -> Option<PathBuf> + 'a;
+
+/// Lowercase per char so indices into the folded text are char indices into the original
+fn fold(text: &str) -> Vec<char> {
+    text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect()
+}
+
+/// Allowed edits for a query token of this length
+fn max_edits(len: usize) -> usize {
+    match len {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Best match of `token` (already folded) in `text`: (quality 0..1, char ranges)
+fn match_token(token: &[char], text: &[char]) -> Option<(f64, Ranges)> {
+    if token.is_empty() || text.is_empty() {
+        return None;
+    }
+
+    // Exact substring, every occurrence highlighted; a hit at a word start ranks higher
+    let mut ranges = Vec::new();
+    let mut word_start = false;
+    let mut i = 0;
+    while i + token.len() <= text.len() {
+        if text[i..i + token.len()] == *token {
+            word_start |= i == 0 || !text[i - 1].is_alphanumeric();
+            ranges.push((i, i + token.len()));
+            i += token.len();
+        } else {
+            i += 1;
+        }
+    }
+    if !ranges.is_empty() {
+        return Some((if word_start { 1.0 } else { 0.8 }, ranges));
+    }
+
+    // Typo tolerance: compare against each word, and against the word's prefix for unfinished input
+    let allowed = max_edits(token.len());
+    if allowed == 0 {
+        return None;
+    }
+    let token_str: String = token.iter().collect();
+    let mut best: Option<(usize, (usize, usize))> = None;
+    let mut start = 0;
+    while start < text.len() {
+        if !text[start].is_alphanumeric() {
+            start += 1;
+            continue;
+        }
+        let end = (start..text.len()).find(|&j| !text[j].is_alphanumeric()).unwrap_or(text.len());
+        let word: String = text[start..end].iter().collect();
+        let mut dist = strsim::damerau_levenshtein(&word, &token_str);
+        if end - start > token.len() {
+            let prefix: String = text[start..start + token.len()].iter().collect();
+            dist = dist.min(strsim::damerau_levenshtein(&prefix, &token_str));
+        }
+        if dist <= allowed && best.is_none_or(|(d, _)| dist < d) {
+            best = Some((dist, (start, end)));
+        }
+        start = end;
+    }
+    best.map(|(dist, range)| (0.6 - 0.15 * dist as f64, vec![range]))
+}
+
+/// Score a candidate; every query token must match some field
+fn score(
+    candidate: &Candidate,
+    tokens: &[Vec<char>],
+    query: &[char],
+    roots: &mut RootLookup,
+) -> Option<(f64, Vec<FieldMatch>)> {
+    let mut fields: Vec<(&str, f64, String)> = vec![("name", NAME_WEIGHT, candidate.name.clone())];
+    if let Some(ref v) = candidate.category {
+        fields.push(("category", CATEGORY_WEIGHT, v.clone()));
+    }
+    if let Some(ref v) = candidate.description {
+        fields.push(("description", DESCRIPTION_WEIGHT, v.clone()));
+    }
+    if let Some(ref v) = candidate.author {
+        fields.push(("author", AUTHOR_WEIGHT, v.clone()));
+    }
+    let folded: Vec<Vec<char>> = fields.iter().map(|(_, _, t)| fold(t)).collect();
+
+    let mut total = 0.0;
+    let mut matches: Vec<FieldMatch> = Vec::new();
+    let mut body: Option<Vec<String>> = None;
+
+    for token in tokens {
+        let mut best: Option<(f64, usize, Ranges)> = None;
+        for (idx, (_, weight, _)) in fields.iter().enumerate() {
+            if let Some((quality, ranges)) = match_token(token, &folded[idx]) {
+                let s = quality * weight;
+                if best.as_ref().is_none_or(|(b, ..)| s > *b) {
+                    best = Some((s, idx, ranges));
+                }
+            }
+        }
+
+        if let Some((s, idx, ranges)) = best {
+            total += s;
+            let (field, _, text) = &fields[idx];
+            match matches.iter_mut().find(|m| m.field == *field) {
+                Some(m) => m.ranges.extend(ranges),
+                None => matches.push(FieldMatch { field: field.to_string(), text: text.clone(), ranges }),
+            }
+            continue;
+        }
+
+        // Metadata didn't match: fall back to the SKILL.md bodies, read only when needed
+        let lines = body.get_or_insert_with(|| read_bodies(&body_paths(&candidate.bodies, roots)));
+        let hit = lines.iter().find_map(|line| match_token(token, &fold(line)).map(|m| (line, m)));
+        let (line, (quality, ranges)) = hit?;
+        total += quality * BODY_WEIGHT;
+        matches.push(FieldMatch { field: "body".to_string(), text: line.trim_end().to_string(), ranges });
+    }
+
+    // Whole query equal to the name beats everything else
+    if fold(&candidate.name) == query {
+        total += NAME_WEIGHT;
+    }
+    for m in &mut matches {
+        m.ranges.sort();
+        m.ranges.dedup();
+    }
+    Some((total, matches))
+}
+
+fn body_paths(bodies: &Bodies, roots: &mut RootLookup) -> Vec<PathBuf> {
+    match bodies {
+        Bodies::Files(paths) => paths.clone(),
+        Bodies::Plugin(plugin) => roots(plugin)
+            .map(|r| {
+                plugin
+                    .skills
+                    .iter()
+                    .map(|s| r.join(s.trim_start_matches("./")).join("SKILL.md"))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+fn read_bodies(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .flat_map(|content| {
+            let body = frontmatter::parse(&content).map(|p| p.body).unwrap_or(content);
+            body.lines().filter(|l| !l.trim().is_empty()).map(str::to_string).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn installed_candidates(app: &tauri::AppHandle) -> Result<Vec<Candidate>, String> {
+    let scan = plugin_scanner::cached_full_scan(app)?;
+    let mut out: Vec<Candidate> = Vec::new();
+    for skill in scan.skills {
+        // One hit per installed copy; agents sharing a folder are listed together
+        if let Some(existing) = out.iter_mut().find(|c| c.path.as_deref() == Some(skill.path.as_str())) {
+            if !existing.agents.contains(&skill.agent_id) {
+                existing.agents.push(skill.agent_id);
+            }
+            continue;
+        }
+        let skill_md = Path::new(&skill.path).join("SKILL.md");
+        out.push(Candidate {
+            origin: "installed",
+            name: skill.name,
+            description: skill.description,
+            category: skill.category,
+            author: None,
+            plugin_name: Some(skill.plugin_name),
+            bodies: Bodies::Files(if skill_md.is_file() { vec![skill_md] } else { vec![PathBuf::from(&skill.path)] }),
+            path: Some(skill.path),
+            agents: vec![skill.agent_id],
+        });
+    }
+    Ok(out)
+}
+
+fn marketplace_candidates(app: &tauri::AppHandle) -> Vec<Candidate> {
+    let Ok(data) = marketplace::get_marketplace_data_cached(app) else {
+        return Vec::new();
+    };
+    data.plugins
+        .into_iter()
+        .map(|plugin| {
+            let author = plugin
+                .author
+                .iter()
+                .chain(plugin.authors.iter().flatten())
+                .map(|a| a.name.clone())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Candidate {
+                origin: "marketplace",
+                name: plugin.name.clone(),
+                description: Some(plugin.description.clone()),
+                category: plugin.category.clone(),
+                author: (!author.is_empty()).then_some(author),
+                plugin_name: Some(plugin.name.clone()),
+                path: None,
+                agents: Vec::new(),
+                bodies: Bodies::Plugin(Box::new(plugin)),
+            }
+        })
+        .collect()
+}
+
+fn local_candidates() -> Vec<Candidate> {
+    // Metadata comes from the scan index, so unchanged skills aren't parsed again
+    let mut stats = scan_index::IndexStats::default();
+    let out = local_skills_registry::registered_directory_paths()
+        .iter()
+        .flat_map(|dir| local_skills_scanner::find_skill_dirs(Path::new(dir)))
+        .filter_map(|dir| {
+            let indexed = scan_index::lookup(&dir, false, &mut stats).ok()?;
+            Some(Candidate {
+                origin: "local",
+                name: indexed.metadata.name?,
+                description: indexed.metadata.description,
+                category: None,
+                author: None,
+                plugin_name: None,
+                bodies: Bodies::Files(vec![dir.join("SKILL.md")]),
+                path: Some(indexed.path),
+                agents: Vec::new(),
+            })
+        })
+        .collect();
+    if let Err(e) = scan_index::flush() {
+        eprintln!("Failed to save scan index: {}", e);
+    }
+    out
+}
+
+/// 统一搜索：已安装 skill、市场插件和本地 skill（模糊匹配，容错拼写）
+#[tauri::command]
+pub fn search_skills(
+    app: tauri::AppHandle,
+    query: String,
+    origins: Option<Vec<String>>, // default: all of "installed" | "marketplace" | "local"
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    let query_chars = fold(query.trim());
+    let tokens: Vec<Vec<char>> = query
+        .split(|c: char| c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .map(fold)
+        .collect();
+    if tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let wants = |origin: &str| origins.as_ref().is_none_or(|o| o.iter().any(|x| x == origin));
+    let mut candidates = Vec::new();
+    if wants("installed") {
+        candidates.extend(installed_candidates(&app)?);
+    }
+    if wants("marketplace") {
+        candidates.extend(marketplace_candidates(&app));
+    }
+    if wants("local") {
+        candidates.extend(local_candidates());
+    }
+
+    let mut roots = plugin_installer::SourceRoots::new(&app);
+    let mut hits: Vec<SearchHit> = candidates
+        .into_iter()
+        .filter_map(|c| {
+            let (score, matches) = score(&c, &tokens, &query_chars, &mut |p| roots.get(p))?;
+            Some(SearchHit {
+                origin: c.origin.to_string(),
+                name: c.name,
+                description: c.description,
+                plugin_name: c.plugin_name,
+                path: c.path,
+                agents: c.agents,
+                score,
+                matches,
+            })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    hits.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(name: &str, description: &str) -> Candidate {
+        Candidate {
+            origin: "local",
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            category: None,
+            author: None,
+            plugin_name: None,
+            path: None,
+            agents: Vec::new(),
+            bodies: Bodies::Files(Vec::new()),
+        }
+    }
+
+    fn search(c: &Candidate, query: &str) -> Option<(f64, Vec<FieldMatch>)> {
+        let tokens: Vec<Vec<char>> = query.split_whitespace().map(fold).collect();
+        score(c, &tokens, &fold(query), &mut |_| None)
+    }
+
+    #[test]
+    fn test_exact_match_ranges_in_chars() {
+        let (_, matches) = search(&candidate("pdf-tools", "Fill PDF forms"), "pdf").unwrap();
+        assert_eq!(matches[0].field, "name");
+        assert_eq!(matches[0].ranges, vec![(0, 3)]);
+    }
+
+    #[test]
+    fn test_typos_are_tolerated() {
+        let c = candidate("spreadsheet", "Work with Excel workbooks");
+        assert!(search(&c, "spreadshet").is_some());
+        assert!(search(&c, "exel").is_some());
+        assert!(search(&c, "xyz").is_none());
+        // every token has to match
+        assert!(search(&c, "excel zzzzzz").is_none());
+    }
+
+    #[test]
+    fn test_name_outweighs_description() {
+        let by_name = search(&candidate("docx", "Word files"), "docx").unwrap().0;
+        let by_desc = search(&candidate("office", "Handles docx files"), "docx").unwrap().0;
+        assert!(by_name > by_desc);
+    }
+}
@@ -0,0 +1,335 @@
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value as YamlValue};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::{frontmatter, local_skills_registry, local_skills_scanner, marketplace, skill_lint};
+
+const BUILTIN_SKILL_MD: &str = "---\nname: skill\ndescription: skill\n---\n\n# {{title}}\n\n{{description}}\n\n## Instructions\n\n1. Describe the steps the agent should follow.\n";
+
+struct BuiltinTemplate {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    files: &'static [(&'static str, &'static str)], // besides SKILL.md
+}
+
+const BUILTIN_TEMPLATES: [BuiltinTemplate; 3] = [
+    BuiltinTemplate { id: "minimal", name: "Minimal", description: "A single SKILL.md", files: &[] },
+    BuiltinTemplate {
+        id: "scripts",
+        name: "With scripts",
+        description: "SKILL.md plus an executable helper in scripts/",
+        files: &[(
+            "scripts/run.py",
+            "#!/usr/bin/env python3\n\"\"\"Helper script for the {{name}} skill.\"\"\"\n\nimport sys\n\n\ndef main() -> int:\n    print(\"{{name}}: not implemented yet\", file=sys.stderr)\n    return 1\n\n\nif __name__ == \"__main__\":\n    sys.exit(main())\n",
+        )],
+    },
+    BuiltinTemplate {
+        id: "references",
+        name: "With references and assets",
+        description: "SKILL.md plus references/ for long docs and assets/ for files the skill uses",
+        files: &[
+            ("references/REFERENCE.md", "# {{title}} reference\n\nDetails the agent loads only when needed.\n"),
+            ("assets/.gitkeep", ""),
+        ],
+    },
+];
+
+// skill 模板
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub builtin: bool,
+    pub path: Option<String>, // custom templates only
+}
+
+// 自定义模板目录中的可选 template.json
+#[derive(Debug, Deserialize, Default)]
+struct TemplateManifest {
+    name: Option<String>,
+    description: Option<String>,
+}
+
+// 创建 skill 的参数
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreateSkillRequest {
+    pub directory: String, // a registered local skills directory
+    pub template: String,  // template id
+    pub name: String,
+    pub description: String,
+    pub license: Option<String>,
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+}
+
+// 创建结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CreatedSkill {
+    pub path: String,
+    pub files: Vec<String>, // relative to the new skill folder
+}
+
+/// `<config>/skillbox-studio/templates/`, one sub-folder per custom template
+pub fn get_templates_dir() -> Result<PathBuf, String> {
+    let dir = dirs::config_dir()
+        .ok_or("Could not find config directory")?
+        .join("skillbox-studio")
+        .join("templates");
+    Ok(dir)
+}
+
+fn custom_templates() -> Vec<SkillTemplate> {
+    let Ok(dir) = get_templates_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut templates: Vec<SkillTemplate> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.join("SKILL.md").is_file())
+        .map(|path| {
+            let id = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let manifest: TemplateManifest = fs::read_to_string(path.join("template.json"))
+                .ok()
+                .and_then(|c| serde_json::from_str(&c).ok())
+                .unwrap_or_default();
+            SkillTemplate {
+                name: manifest.name.unwrap_or_else(|| id.clone()),
+                description: manifest.description.unwrap_or_default(),
+                id,
+                builtin: false,
+                path: Some(path.to_string_lossy().to_string()),
+            }
+        })
+        .collect();
+    templates.sort_by(|a, b| a.id.cmp(&b.id));
+    templates
+}
+
+/// Files of a template as (relative path, content). Custom templates override built-ins with the same id.
+fn template_files(id: &str) -> Result<Vec<(String, Vec<u8>)>, String> {
+    if let Some(custom) = custom_templates().into_iter().find(|t| t.id == id) {
+        let root = PathBuf::from(custom.path.unwrap_or_default());
+        let mut files = Vec::new();
+        for entry in ignore::WalkBuilder::new(&root).standard_filters(false).build().flatten() {
+            let path = entry.path();
+            if !entry.file_type().is_some_and(|t| t.is_file()) || path == root.join("template.json") {
+                continue;
+            }
+            let rel = path.strip_prefix(&root).unwrap_or(path).to_string_lossy().replace('\\', "/");
+            let content = fs::read(path).map_err(|e| format!("Failed to read template file {}: {}", rel, e))?;
+            files.push((rel, content));
+        }
+        return Ok(files);
+    }
+
+    let builtin = BUILTIN_TEMPLATES
+        .iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("Unknown template: {}", id))?;
+    let mut files = vec![("SKILL.md".to_string(), BUILTIN_SKILL_MD.as_bytes().to_vec())];
+    files.extend(builtin.files.iter().map(|(p, c)| (p.to_string(), c.as_bytes().to_vec())));
+    Ok(files)
+}
+
+/// "pdf-form-filler" -> "Pdf Form Filler"
+fn title_of(name: &str) -> String {
+    name.split('-')
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn fill_placeholders(text: &str, req: &CreateSkillRequest) -> String {
+    text.replace("{{name}}", &req.name)
+        .replace("{{title}}", &title_of(&req.name))
+        .replace("{{description}}", req.description.trim())
+        .replace("{{license}}", req.license.as_deref().unwrap_or(""))
+}
+
+/// Rebuild the SKILL.md frontmatter from the request, keeping any other keys the template defines
+fn render_skill_md(template: &str, req: &CreateSkillRequest) -> Result<String, String> {
+    let parsed = frontmatter::parse(template).map_err(|e| format!("Invalid template SKILL.md: {}", e))?;
+    let mut mapping: Mapping = serde_yaml::from_str(&parsed.raw_frontmatter).unwrap_or_default();
+
+    let set = |m: &mut Mapping, key: &str, value: YamlValue| {
+        m.insert(YamlValue::String(key.to_string()), value);
+    };
+    set(&mut mapping, "name", YamlValue::String(req.name.clone()));
+    set(&mut mapping, "description", YamlValue::String(req.description.trim().to_string()));
+    if let Some(ref license) = req.license {
+        set(&mut mapping, "license", YamlValue::String(license.clone()));
+    }
+    if !req.allowed_tools.is_empty() {
+        let tools = req.allowed_tools.iter().cloned().map(YamlValue::String).collect();
+        set(&mut mapping, "allowed-tools", YamlValue::Sequence(tools));
+    }
+    // name and description first, as agents and readers expect
+    let mut ordered = Mapping::new();
+    for key in ["name", "description"] {
+        if let Some(v) = mapping.remove(key) {
+            set(&mut ordered, key, v);
+        }
+    }
+    ordered.extend(mapping);
+
+    let yaml = serde_yaml::to_string(&ordered).map_err(|e| format!("Failed to write frontmatter: {}", e))?;
+    Ok(format!("---\n{}---\n\n{}", yaml, fill_placeholders(&parsed.body, req)))
+}
+
+/// Names already taken by a local skill or a marketplace skill (folder or frontmatter name)
+fn taken_names(app: &tauri::AppHandle) -> Vec<String> {
+    let mut names: Vec<String> = local_skills_registry::registered_directory_paths()
+        .iter()
+        .flat_map(|dir| local_skills_scanner::collect_local_skills(Path::new(dir)).0)
+        .flat_map(|s| {
+            let folder = Path::new(&s.path).file_name().map(|n| n.to_string_lossy().to_string());
+            [Some(s.name), folder]
+        })
+        .flatten()
+        .collect();
+    if let Ok(data) = marketplace::get_marketplace_data_cached(app) {
+        for plugin in data.plugins {
+            names.extend(plugin.skills.iter().filter_map(|s| {
+                Path::new(s.trim_start_matches("./")).file_name().map(|n| n.to_string_lossy().to_string())
+            }));
+        }
+    }
+    names
+}
+
+#[cfg(unix)]
+fn copy_mode(from: &Path, to: &Path) {
+    if let Ok(meta) = fs::metadata(from) {
+        let _ = fs::set_permissions(to, meta.permissions());
+    }
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o755));
+}
+
+/// 列出内置与自定义模板
+#[tauri::command]
+pub fn list_skill_templates() -> Vec<SkillTemplate> {
+    let custom = custom_templates();
+    let mut templates: Vec<SkillTemplate> = BUILTIN_TEMPLATES
+        .iter()
+        .filter(|t| !custom.iter().any(|c| c.id == t.id))
+        .map(|t| SkillTemplate {
+            id: t.id.to_string(),
+            name: t.name.to_string(),
+            description: t.description.to_string(),
+            builtin: true,
+            path: None,
+        })
+        .collect();
+    templates.extend(custom);
+    templates
+}
+
+/// 从模板在已注册的本地目录中创建新 skill
+#[tauri::command]
+pub fn create_skill_from_template(app: tauri::AppHandle, request: CreateSkillRequest) -> Result<CreatedSkill, String> {
+    let name = request.name.trim();
+    if name.is_empty() || skill_lint::normalize_name(name) != name {
+        return Err(format!(
+            "Invalid skill name '{}', use lowercase letters, digits and hyphens (e.g. '{}')",
+            name,
+            skill_lint::normalize_name(name)
+        ));
+    }
+    if request.description.trim().is_empty() {
+        return Err("Description is required".to_string());
+    }
+
+    let directory = Path::new(&request.directory);
+    let registered = local_skills_registry::registered_directory_paths();
+    if !registered.iter().any(|d| Path::new(d) == directory) {
+        return Err(format!("{} is not a registered local skills directory", request.directory));
+    }
+    if taken_names(&app).iter().any(|n| n == name) {
+        return Err(format!("A skill named '{}' already exists", name));
+    }
+    let target = directory.join(name);
+    if target.exists() {
+        return Err(format!("{} already exists", target.display()));
+    }
+
+    let req = CreateSkillRequest { name: name.to_string(), ..request };
+    let files = template_files(&req.template)?;
+    let template_root = custom_templates().into_iter().find(|t| t.id == req.template).and_then(|t| t.path);
+
+    let write_all = || -> Result<Vec<String>, String> {
+        let mut written = Vec::new();
+        for (rel, content) in &files {
+            let path = target.join(rel);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            let content = match String::from_utf8(content.clone()) {
+                Ok(text) if rel == "SKILL.md" => render_skill_md(&text, &req)?.into_bytes(),
+                Ok(text) => fill_placeholders(&text, &req).into_bytes(),
+                Err(_) => content.clone(), // binary asset
+            };
+            fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+            #[cfg(unix)]
+            match template_root {
+                Some(ref root) => copy_mode(&Path::new(root).join(rel), &path),
+                None if rel.starts_with("scripts/") => set_executable(&path),
+                None => {}
+            }
+            written.push(rel.clone());
+        }
+        Ok(written)
+    };
+
+    match write_all() {
+        Ok(files) => Ok(CreatedSkill { path: target.to_string_lossy().to_string(), files }),
+        Err(e) => {
+            // Don't leave a half-written skill behind
+            let _ = fs::remove_dir_all(&target);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frontmatter_filled_from_request() {
+        let req = CreateSkillRequest {
+            directory: String::new(),
+            template: "minimal".to_string(),
+            name: "pdf-forms".to_string(),
+            description: "Fill PDF forms: use when the user asks about forms.".to_string(),
+            license: Some("MIT".to_string()),
+            allowed_tools: vec!["Read".to_string()],
+        };
+        let template = "---\nname: x\nmetadata:\n  team: docs\n---\n# {{title}}\n";
+        let rendered = render_skill_md(template, &req).unwrap();
+
+        let parsed = frontmatter::parse(&rendered).unwrap();
+        let fm = parsed.frontmatter.unwrap();
+        assert_eq!(parsed.keys[..2], ["name".to_string(), "description".to_string()]);
+        assert_eq!(fm.description.as_deref(), Some("Fill PDF forms: use when the user asks about forms."));
+        assert_eq!(fm.allowed_tools, vec!["Read"]);
+        assert_eq!(fm.metadata["team"], "docs");
+        assert_eq!(parsed.body, "# Pdf Forms\n");
+    }
+}
@@ -0,0 +1,304 @@
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::commands::{
+    agent_config, local_skills_registry, local_skills_scanner, plugin_scanner, project_discovery, scan_index,
+};
+
+/// Event emitted to the frontend for every skill that changed on disk
+pub const SKILL_CHANGED_EVENT: &str = "skill-changed";
+
+const DEBOUNCE_MS: u64 = 500;
+
+// 被监听的根目录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WatchedRoot {
+    pub path: String,
+    pub kind: String, // "agent_global" | "agent_project" | "local_directory"
+    pub agent_id: Option<String>,
+    pub project_path: Option<String>,
+}
+
+// skill 变更事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SkillChangeEvent {
+    pub change: String, // "added" | "changed" | "removed"
+    pub skill_name: String,
+    pub path: String,
+    pub root: WatchedRoot,
+}
+
+// 当前监听器及其根目录
+struct ActiveWatcher {
+    _debouncer: Debouncer<RecommendedWatcher>,
+    roots: Vec<String>,
+}
+
+static WATCHER: Lazy<Mutex<Option<ActiveWatcher>>> = Lazy::new(|| Mutex::new(None));
+
+/// Every directory we know skills can live in and that exists right now
+fn collect_roots() -> Vec<WatchedRoot> {
+    let mut roots = Vec::new();
+    let agents = agent_config::all_agents();
+
+    for agent in &agents {
+        if let Some(path) = agent_config::get_agent_global_path(&agent.id) {
+            roots.push(WatchedRoot {
+                path: path.to_string_lossy().to_string(),
+                kind: "agent_global".to_string(),
+                agent_id: Some(agent.id.clone()),
+                project_path: None,
+            });
+        }
+    }
+
+    for project in project_discovery::known_project_paths() {
+        for agent in &agents {
+            if let Some(path) = agent_config::get_agent_project_path(&agent.id, &project) {
+                roots.push(WatchedRoot {
+                    path: path.to_string_lossy().to_string(),
+                    kind: "agent_project".to_string(),
+                    agent_id: Some(agent.id.clone()),
+                    project_path: Some(project.clone()),
+                });
+            }
+        }
+    }
+
+    for dir in local_skills_registry::registered_directory_paths() {
+        roots.push(WatchedRoot {
+            path: dir,
+            kind: "local_directory".to_string(),
+            agent_id: None,
+            project_path: None,
+        });
+    }
+
+    // Several agents share a directory (e.g. `.agents/skills/`); watch it once
+    let mut seen = std::collections::HashSet::new();
+    roots.retain(|r| Path::new(&r.path).is_dir() && seen.insert(r.path.clone()));
+    roots
+}
+
+/// Skills in local directories never pass through the scan index the way agent folders do,
+/// so index them up front; otherwise their first edit would be reported as "added".
+fn index_local_skills(roots: &[WatchedRoot]) -> usize {
+    let mut stats = scan_index::IndexStats::default();
+    roots
+        .iter()
+        .filter(|r| r.kind == "local_directory")
+        .flat_map(|r| local_skills_scanner::find_skill_dirs(Path::new(&r.path)))
+        .filter(|dir| scan_index::lookup(dir, false, &mut stats).is_ok())
+        .count()
+}
+
+/// Map a changed path to the skill folder it belongs to and classify the change
+fn classify(path: &Path, root: &WatchedRoot) -> Option<SkillChangeEvent> {
+    let root_path = Path::new(&root.path);
+
+    // The outermost folder below the root that holds a SKILL.md is the skill
+    let live_skill = path
+        .ancestors()
+        .take_while(|p| *p != root_path && p.starts_with(root_path))
+        .filter(|p| p.join("SKILL.md").is_file())
+        .last()
+        .map(Path::to_path_buf);
+
+    let (change, skill_dir) = match live_skill {
+        Some(dir) => {
+            let known = scan_index::indexed_ancestors(&dir).contains(&dir);
+            (if known { "changed" } else { "added" }, dir)
+        }
+        None => {
+            // Nothing left on disk: it was a skill only if the index knew it
+            let gone = scan_index::indexed_ancestors(path)
+                .into_iter()
+                .filter(|p| p.starts_with(root_path) && !p.join("SKILL.md").is_file())
+                .min_by_key(|p| p.components().count())?;
+            ("removed", gone)
+        }
+    };
+
+    Some(SkillChangeEvent {
+        change: change.to_string(),
+        skill_name: skill_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: skill_dir.to_string_lossy().to_string(),
+        root: root.clone(),
+    })
+}
+
+fn handle_events(app: &tauri::AppHandle, roots: &[WatchedRoot], paths: Vec<PathBuf>) {
+    // One event per skill per debounce window, however many files changed inside it
+    let mut changes: HashMap<String, SkillChangeEvent> = HashMap::new();
+    for path in paths {
+        let Some(root) = roots
+            .iter()
+            .filter(|r| path.starts_with(&r.path))
+            .max_by_key(|r| r.path.len())
+        else {
+            continue;
+        };
+        if let Some(event) = classify(&path, root) {
+            changes.insert(event.path.clone(), event);
+        }
+    }
+
+    if !changes.is_empty() {
+        plugin_scanner::invalidate_full_scan();
+    }
+    for event in changes.into_values() {
+        let skill_path = Path::new(&event.path);
+        if event.change == "removed" {
+            scan_index::invalidate(skill_path);
+        } else if let Err(e) = scan_index::lookup(skill_path, true, &mut scan_index::IndexStats::default()) {
+            eprintln!("Failed to refresh scan index for {}: {}", event.path, e);
+        }
+        if let Err(e) = app.emit(SKILL_CHANGED_EVENT, &event) {
+            eprintln!("Failed to emit skill change: {}", e);
+        }
+    }
+
+    if let Err(e) = scan_index::flush() {
+        eprintln!("Failed to save scan index: {}", e);
+    }
+}
+
+/// (Re)start watching all skill directories. Call again after the set of directories changed.
+pub fn start(app: &tauri::AppHandle) -> Result<Vec<WatchedRoot>, String> {
+    let roots = collect_roots();
+    let handler_roots = roots.clone();
+    let index_roots = roots.clone();
+    let handler_app = app.clone();
+
+    let mut debouncer = new_debouncer(Duration::from_millis(DEBOUNCE_MS), move |res: DebounceEventResult| {
+        match res {
+            Ok(events) => handle_events(&handler_app, &handler_roots, events.into_iter().map(|e| e.path).collect()),
+            Err(e) => eprintln!("Skill watcher error: {}", e),
+        }
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+    for root in &roots {
+        if let Err(e) = debouncer.watcher().watch(Path::new(&root.path), RecursiveMode::Recursive) {
+            eprintln!("Failed to watch {}: {}", root.path, e);
+        }
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        index_local_skills(&index_roots);
+        if let Err(e) = scan_index::flush() {
+            eprintln!("Failed to save scan index: {}", e);
+        }
+    });
+
+    // Dropping the previous debouncer stops its watches
+    let mut slot = WATCHER.lock().map_err(|_| "Watcher lock poisoned".to_string())?;
+    *slot = Some(ActiveWatcher {
+        _debouncer: debouncer,
+        roots: roots.iter().map(|r| r.path.clone()).collect(),
+    });
+    Ok(roots)
+}
+
+/// Restart the running watcher when the set of skill directories changed, e.g. after a
+/// project or local directory was registered or an install created an agent's skills folder.
+/// Nothing is restarted while the watcher is stopped; the cached full scan is dropped
+/// either way, since every caller just changed something on disk.
+pub fn refresh(app: &tauri::AppHandle) {
+    plugin_scanner::invalidate_full_scan();
+    let watched = match WATCHER.lock() {
+        Ok(slot) => slot.as_ref().map(|w| w.roots.clone()),
+        Err(_) => None,
+    };
+    let Some(watched) = watched else {
+        return;
+    };
+    let wanted: Vec<String> = collect_roots().into_iter().map(|r| r.path).collect();
+    if wanted != watched {
+        if let Err(e) = start(app) {
+            eprintln!("Failed to restart skill watcher: {}", e);
+        }
+    }
+}
+
+/// 启动（或重启）技能目录监听
+#[tauri::command]
+pub fn start_skill_watcher(app: tauri::AppHandle) -> Result<Vec<WatchedRoot>, String> {
+    start(&app)
+}
+
+/// 停止技能目录监听
+#[tauri::command]
+pub fn stop_skill_watcher() -> Result<bool, String> {
+    let mut slot = WATCHER.lock().map_err(|_| "Watcher lock poisoned".to_string())?;
+    Ok(slot.take().is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn root(path: &Path, kind: &str) -> WatchedRoot {
+        WatchedRoot {
+            path: path.to_string_lossy().to_string(),
+            kind: kind.to_string(),
+            agent_id: None,
+            project_path: None,
+        }
+    }
+
+    fn skill(dir: &Path) {
+        fs::create_dir_all(dir.join("refs")).unwrap();
+        fs::write(dir.join("SKILL.md"), "---\nname: demo\ndescription: Demo\n---\n").unwrap();
+        fs::write(dir.join("refs/notes.md"), "notes").unwrap();
+    }
+
+    #[test]
+    fn test_classify_added_changed_removed() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let watched = root(tmp.path(), "agent_global");
+        let dir = tmp.path().join("demo");
+        skill(&dir);
+
+        let event = classify(&dir.join("refs/notes.md"), &watched).unwrap();
+        assert_eq!(event.change, "added");
+        assert_eq!(event.skill_name, "demo");
+        assert_eq!(event.path, dir.to_string_lossy());
+
+        scan_index::lookup(&dir, false, &mut scan_index::IndexStats::default()).unwrap();
+        assert_eq!(classify(&dir.join("SKILL.md"), &watched).unwrap().change, "changed");
+
+        fs::remove_dir_all(&dir).unwrap();
+        let event = classify(&dir.join("SKILL.md"), &watched).unwrap();
+        assert_eq!(event.change, "removed");
+        assert_eq!(event.path, dir.to_string_lossy());
+        scan_index::invalidate(&dir);
+
+        // Files outside any skill are not reported
+        fs::write(tmp.path().join("README.md"), "readme").unwrap();
+        assert!(classify(&tmp.path().join("README.md"), &watched).is_none());
+    }
+
+    #[test]
+    fn test_local_directory_skills_are_known_once_indexed() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let watched = root(tmp.path(), "local_directory");
+        let dir = tmp.path().join("group/demo");
+        skill(&dir);
+
+        assert_eq!(index_local_skills(std::slice::from_ref(&watched)), 1);
+        assert_eq!(classify(&dir.join("refs/notes.md"), &watched).unwrap().change, "changed");
+        scan_index::invalidate(&dir);
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What a directory walk does when it meets a symbolic link.
+/// Scans, sizes and hashes follow links so they describe what an install copies;
+/// installs follow them by default so the copied skill is self-contained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkPolicy {
+    Follow,   // treat the link as its target (loops are detected and cut)
+    Preserve, // keep the link itself: copied as a link, counted by its own size
+    Skip,     // act as if the link wasn't there
+}
+
+impl LinkPolicy {
+    /// Parse a policy sent by the frontend ("follow" | "preserve" | "skip")
+    pub fn parse(value: Option<&str>, default: LinkPolicy) -> Result<LinkPolicy, String> {
+        match value {
+            None => Ok(default),
+            Some("follow") => Ok(LinkPolicy::Follow),
+            Some("preserve") => Ok(LinkPolicy::Preserve),
+            Some("skip") => Ok(LinkPolicy::Skip),
+            Some(other) => Err(format!("Unknown symlink policy: {}", other)),
+        }
+    }
+}
+
+/// Most entries one walk visits before giving up. No skill comes close; a walk this large
+/// means a followed link points at something like `~` or `/`.
+pub const MAX_WALK_ENTRIES: usize = 100_000;
+
+/// The real directories between a walk's root and its current position. Only a link back
+/// to one of them (a loop) is cut; two links to the same folder, or a folder and a link
+/// to it, are both walked in full. Also keeps the walk's entry budget.
+pub struct LoopGuard {
+    chain: Vec<PathBuf>,
+    entries: usize,
+    limit: usize,
+    cuts: usize,
+}
+
+impl Default for LoopGuard {
+    fn default() -> Self {
+        Self::with_limit(MAX_WALK_ENTRIES)
+    }
+}
+
+impl LoopGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_limit(limit: usize) -> Self {
+        LoopGuard { chain: Vec::new(), entries: 0, limit, cuts: 0 }
+    }
+
+    /// Step into `dir`. `false` when it is an ancestor of the current position (or can't be resolved).
+    pub fn enter(&mut self, dir: &Path) -> bool {
+        match fs::canonicalize(dir) {
+            Ok(real) if !self.chain.contains(&real) => {
+                self.chain.push(real);
+                true
+            }
+            Ok(_) => {
+                self.cuts += 1;
+                false
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// How many loops were cut so far; a walk whose count didn't change saw the whole subtree
+    pub fn cuts(&self) -> usize {
+        self.cuts
+    }
+
+    /// Count `n` entries against the walk's budget, failing once it is used up
+    pub fn count(&mut self, n: usize) -> io::Result<()> {
+        self.entries += n;
+        if self.entries > self.limit {
+            return Err(io::Error::other(format!(
+                "More than {} files and folders below this folder, a link probably points outside the skill",
+                self.limit
+            )));
+        }
+        Ok(())
+    }
+
+    /// Step back out of the directory entered last
+    pub fn leave(&mut self) {
+        self.chain.pop();
+    }
+
+    /// Run `walk` inside `dir`, or return `None` when entering it would loop
+    pub fn descend<T>(&mut self, dir: &Path, walk: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        if !self.enter(dir) {
+            return None;
+        }
+        let result = walk(self);
+        self.leave();
+        Some(result)
+    }
+}
+
+pub fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+}
+
+/// When `path` (or a folder between `root` and `path`) is a symlink, the real location it points to
+pub fn linked_target(root: &Path, path: &Path) -> Option<PathBuf> {
+    let rel = path.strip_prefix(root).ok()?;
+    let mut current = root.to_path_buf();
+    for component in rel.components() {
+        current.push(component);
+        if is_symlink(&current) {
+            return fs::canonicalize(path).ok();
+        }
+    }
+    None
+}
+
+/// Total size of the files below `dir`
+pub fn dir_size(dir: &Path, policy: LinkPolicy) -> io::Result<u64> {
+    dir_size_limited(dir, policy, LoopGuard::new())
+}
+
+fn dir_size_limited(dir: &Path, policy: LinkPolicy, mut guard: LoopGuard) -> io::Result<u64> {
+    guard.enter(dir);
+    dir_size_inner(dir, policy, &mut guard, &mut HashMap::new())
+}
+
+// 已计算过的真实目录大小：菱形链接只遍历一次
+type SizeMemo = HashMap<PathBuf, u64>;
+
+fn dir_size_inner(dir: &Path, policy: LinkPolicy, guard: &mut LoopGuard, memo: &mut SizeMemo) -> io::Result<u64> {
+    let mut size = 0u64;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let ty = entry.file_type()?;
+        guard.count(1)?;
+
+        if ty.is_symlink() {
+            match policy {
+                LinkPolicy::Skip => {}
+                LinkPolicy::Preserve => size += fs::symlink_metadata(&path)?.len(),
+                LinkPolicy::Follow => match fs::metadata(&path) {
+                    Ok(m) if m.is_dir() => size += subdir_size(&path, policy, guard, memo)?,
+                    Ok(m) => size += m.len(),
+                    Err(_) => {} // dangling link
+                },
+            }
+        } else if ty.is_dir() {
+            size += subdir_size(&path, policy, guard, memo)?;
+        } else {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Size of a folder met during a size walk. A real folder already sized through another
+/// path counts again without being re-read, so a diamond of links isn't walked exponentially.
+/// Results that depended on a loop being cut are not reused, the cut depends on the path taken.
+fn subdir_size(path: &Path, policy: LinkPolicy, guard: &mut LoopGuard, memo: &mut SizeMemo) -> io::Result<u64> {
+    let real = fs::canonicalize(path).ok();
+    if let Some(size) = real.as_ref().and_then(|r| memo.get(r)) {
+        return Ok(*size);
+    }
+    let cuts = guard.cuts();
+    let Some(size) = guard.descend(path, |g| dir_size_inner(path, policy, g, memo)).transpose()? else {
+        return Ok(0);
+    };
+    if let Some(real) = real.filter(|_| guard.cuts() == cuts) {
+        memo.insert(real, size);
+    }
+    Ok(size)
+}
+
+/// Recursively copy `src` into `dst`
+pub fn copy_dir(src: &Path, dst: &Path, policy: LinkPolicy) -> io::Result<()> {
+    copy_dir_limited(src, dst, policy, LoopGuard::new())
+}
+
+fn copy_dir_limited(src: &Path, dst: &Path, policy: LinkPolicy, mut guard: LoopGuard) -> io::Result<()> {
+    guard.enter(src);
+    copy_dir_inner(src, dst, policy, &mut guard)
+}
+
+fn copy_dir_inner(src: &Path, dst: &Path, policy: LinkPolicy, guard: &mut LoopGuard) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = dst.join(entry.file_name());
+        let ty = entry.file_type()?;
+        guard.count(1)?;
+
+        if ty.is_symlink() {
+            match policy {
+                LinkPolicy::Skip => {}
+                LinkPolicy::Preserve => {
+                    if fs::symlink_metadata(&target).is_ok() {
+                        fs::remove_file(&target)?;
+                    }
+                    create_symlink(&fs::read_link(&path)?, &target, path.is_dir())?;
+                }
+                LinkPolicy::Follow => match fs::metadata(&path) {
+                    Ok(m) if m.is_dir() => {
+                        guard.descend(&path, |g| copy_dir_inner(&path, &target, policy, g)).transpose()?;
+                    }
+                    Ok(_) => {
+                        fs::copy(&path, &target)?;
+                    }
+                    Err(_) => {} // dangling link, nothing to copy
+                },
+            }
+        } else if ty.is_dir() {
+            guard.descend(&path, |g| copy_dir_inner(&path, &target, policy, g)).transpose()?;
+        } else {
+            fs::copy(&path, &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_symlink(original: &Path, link: &Path, _is_dir: bool) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn create_symlink(original: &Path, link: &Path, is_dir: bool) -> io::Result<()> {
+    if is_dir {
+        std::os::windows::fs::symlink_dir(original, link)
+    } else {
+        std::os::windows::fs::symlink_file(original, link)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loop_is_cut_and_links_respect_policy() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        let src = root.join("src");
+        fs::create_dir_all(src.join("refs")).unwrap();
+        fs::write(src.join("SKILL.md"), "12345").unwrap();
+        fs::write(src.join("refs/a.md"), "abc").unwrap();
+        std::os::unix::fs::symlink(&src, src.join("refs/loop")).unwrap();
+        std::os::unix::fs::symlink(src.join("SKILL.md"), src.join("alias.md")).unwrap();
+
+        assert_eq!(dir_size(&src, LinkPolicy::Skip).unwrap(), 8);
+        assert_eq!(dir_size(&src, LinkPolicy::Follow).unwrap(), 13);
+
+        copy_dir(&src, &root.join("followed"), LinkPolicy::Follow).unwrap();
+        assert!(!is_symlink(&root.join("followed/alias.md")));
+        assert!(!root.join("followed/refs/loop").exists());
+
+        copy_dir(&src, &root.join("preserved"), LinkPolicy::Preserve).unwrap();
+        assert!(is_symlink(&root.join("preserved/refs/loop")));
+
+        assert!(linked_target(root, &src.join("refs/loop")).is_some());
+        assert!(linked_target(root, &src.join("refs")).is_none());
+    }
+
+    #[test]
+    fn test_links_to_the_same_folder_are_all_copied() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        let shared = root.join("shared");
+        fs::create_dir_all(shared.join("nested")).unwrap();
+        fs::write(shared.join("a.md"), "abc").unwrap();
+        fs::write(shared.join("nested/b.md"), "de").unwrap();
+
+        let src = root.join("src");
+        fs::create_dir_all(&src).unwrap();
+        std::os::unix::fs::symlink(&shared, src.join("one")).unwrap();
+        std::os::unix::fs::symlink(&shared, src.join("two")).unwrap();
+        std::os::unix::fs::symlink(&shared, shared.join("nested/up")).unwrap(); // loop
+
+        assert_eq!(dir_size(&src, LinkPolicy::Follow).unwrap(), 10);
+        copy_dir(&src, &root.join("copy"), LinkPolicy::Follow).unwrap();
+        for link in ["one", "two"] {
+            let copied = root.join("copy").join(link);
+            assert_eq!(fs::read_to_string(copied.join("a.md")).unwrap(), "abc");
+            assert_eq!(fs::read_to_string(copied.join("nested/b.md")).unwrap(), "de");
+            assert!(!copied.join("nested/up").exists());
+        }
+    }
+
+    // levels/0 .. levels/n, each with a 1-byte file and two links to the next level
+    fn diamond(root: &Path, levels: usize) -> PathBuf {
+        for i in 0..=levels {
+            let dir = root.join(format!("levels/{}", i));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("f"), "x").unwrap();
+            if i > 0 {
+                let prev = root.join(format!("levels/{}", i - 1));
+                std::os::unix::fs::symlink(&dir, prev.join("a")).unwrap();
+                std::os::unix::fs::symlink(&dir, prev.join("b")).unwrap();
+            }
+        }
+        root.join("levels/0")
+    }
+
+    #[test]
+    fn test_link_to_a_large_tree_hits_the_budget() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        let big = root.join("big");
+        fs::create_dir_all(&big).unwrap();
+        for i in 0..50 {
+            fs::write(big.join(format!("{}.md", i)), "x").unwrap();
+        }
+        let src = root.join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("SKILL.md"), "x").unwrap();
+        std::os::unix::fs::symlink(&big, src.join("home")).unwrap();
+
+        assert!(dir_size_limited(&src, LinkPolicy::Follow, LoopGuard::with_limit(20)).is_err());
+        assert!(copy_dir_limited(&src, &root.join("copy"), LinkPolicy::Follow, LoopGuard::with_limit(20)).is_err());
+        assert_eq!(dir_size_limited(&src, LinkPolicy::Follow, LoopGuard::with_limit(100)).unwrap(), 51);
+        // Not following the link keeps the walk small
+        assert_eq!(dir_size_limited(&src, LinkPolicy::Skip, LoopGuard::with_limit(20)).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_diamond_of_links_is_sized_without_rewalking() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let src = diamond(tmp.path(), 30);
+
+        // Every path counts, as a copy would hold them all, but each level is read once
+        assert_eq!(dir_size(&src, LinkPolicy::Follow).unwrap(), (1u64 << 31) - 1);
+        // Copying really would write every path, so the budget stops it
+        let copy = tmp.path().join("copy");
+        assert!(copy_dir_limited(&src, &copy, LinkPolicy::Follow, LoopGuard::with_limit(1_000)).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 mod commands;
 mod types;
 
-use commands::{agent_config, marketplace, plugin_installer, plugin_scanner, install_history, system, skill_metadata, translator};
+use commands::{agent_config, agent_detector, marketplace, plugin_installer, plugin_scanner, install_history, system, skill_metadata, translator};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -9,12 +9,51 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .setup(|app| {
+            // Pick up agent definitions shipped with configured marketplaces
+            agent_config::reload_agents(app.handle());
+            // Seed the projects registry from install history on first run
+            if let Err(e) = commands::projects_registry::import_install_history() {
+                eprintln!("Failed to import projects from install history: {}", e);
+            }
+            // Push live changes in skill directories to the frontend
+            if let Err(e) = commands::skill_watcher::start(app.handle()) {
+                eprintln!("Failed to start skill watcher: {}", e);
+            }
+            // Clean up clones left behind by an add that never finished
+            if let Err(e) = commands::git_manager::remove_orphan_clones(app.handle()) {
+                eprintln!("Failed to remove orphan clones: {}", e);
+            }
+            // Keep marketplace repositories current in the background
+            commands::refresh_scheduler::start(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             agent_config::get_agents,
+            agent_config::get_agent_path_diagnostics,
+            agent_detector::detect_agents,
             marketplace::get_marketplace_data,
+            commands::marketplace_query::query_marketplace,
             plugin_installer::install_plugin,
+            commands::compatibility::plan_plugin_install,
+            commands::compatibility::plan_local_skill_install,
             plugin_scanner::scan_installed_plugins,
             plugin_scanner::search_installed_plugins,
+            commands::skill_search::search_skills,
+            commands::scan_index::clear_scan_index,
+            commands::frontmatter::read_skill_frontmatter,
+            commands::skill_health::check_skills_health,
+            commands::skill_lint::list_lint_rules,
+            commands::skill_lint::lint_skill,
+            commands::skill_lint::fix_skill_lint,
+            commands::skill_conflicts::analyze_skill_conflicts,
+            commands::skill_diff::diff_skill_dirs,
+            commands::skill_diff::diff_skill_with_source,
+            commands::skill_watcher::start_skill_watcher,
+            commands::skill_watcher::stop_skill_watcher,
+            commands::project_discovery::discover_projects,
+            commands::project_discovery::list_known_projects,
+            commands::project_discovery::remove_known_project,
             install_history::get_install_history,
             install_history::clear_install_history,
             install_history::get_history_stats,
@@ -24,12 +63,24 @@ pub fn run() {
             // Git & Config
             commands::git_manager::add_marketplace_repository,
             commands::git_manager::validate_marketplace_repository,
+            commands::git_manager::update_marketplace_repository,
+            commands::git_manager::update_all_marketplace_repositories,
+            commands::git_manager::get_repository_status,
+            commands::credential_store::set_repository_credential,
+            commands::credential_store::remove_repository_credential,
+            commands::credential_store::get_repository_credential_info,
+            commands::refresh_scheduler::refresh_marketplaces_now,
+            commands::refresh_scheduler::get_refresh_status,
+            commands::refresh_scheduler::report_user_activity,
             commands::config_manager::get_app_config,
             commands::config_manager::save_app_config,
             commands::config_manager::update_general_settings,
+            commands::config_manager::update_workspace_settings,
+            commands::config_manager::update_refresh_settings,
             commands::config_manager::add_repository_to_config,
             commands::config_manager::remove_repository_from_config,
             commands::config_manager::update_repository_enabled,
+            commands::config_manager::update_repository_pinned,
             commands::config_manager::update_repository_in_config,
             // Local Skills
             commands::local_skills_scanner::scan_local_skills,
@@ -38,6 +89,17 @@ pub fn run() {
             commands::local_skills_registry::list_registered_directories,
             commands::local_skills_registry::update_local_directory,
             commands::local_skills_installer::install_local_skill,
+            commands::skill_templates::list_skill_templates,
+            commands::skill_templates::create_skill_from_template,
+            commands::skill_editor::list_skill_files,
+            commands::skill_editor::read_skill_file,
+            commands::skill_editor::write_skill_file,
+            commands::skill_editor::undo_skill_file_edit,
+            // Projects
+            commands::projects_registry::register_project,
+            commands::projects_registry::unregister_project,
+            commands::projects_registry::list_registered_projects,
+            commands::projects_registry::update_project,
             // Translation
             translator::translate,
             translator::translate_batch,
@@ -15,6 +15,28 @@ pub struct InstalledPlugin {
     pub size_bytes: u64,
     pub paths_by_agent: HashMap<String, String>, // agent_id -> path
     pub source_type: Option<String>, // "Marketplace" or "LocalDirectory"
+    #[serde(default)]
+    pub linked_paths: HashMap<String, String>, // installed path -> real target, for skills that are symlinks
+}
+
+// 单个已安装 skill（按代理、按路径）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstalledSkill {
+    pub name: String, // skill 目录名
+    pub plugin_name: String,
+    pub grouped_by: String, // "provenance" | "marketplace" | "standalone"
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub version: Option<String>,
+    pub agent_id: String,
+    pub scope: String, // "global" 或 "project"
+    pub project_path: Option<String>,
+    pub path: String,
+    pub size_bytes: u64,
+    pub installed_at: String,
+    pub content_hash: String,
+    pub source_type: Option<String>,
+    pub link_target: Option<String>,
 }
 
 // 插件安装位置信息
@@ -32,6 +54,12 @@ pub struct ScanResult {
     pub by_scope: ScanSummary,
     pub by_agent: HashMap<String, usize>,
     pub plugins: Vec<InstalledPlugin>,
+    #[serde(default)]
+    pub skills: Vec<InstalledSkill>, // per-skill view of the same scan
+    #[serde(default)]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub index_stats: crate::commands::scan_index::IndexStats,
 }
 
 // 扫描统计
@@ -99,6 +127,10 @@ pub struct AppConfig {
     pub agents: AgentsConfig,
     #[serde(default)]
     pub advanced: AdvancedSettings,
+    #[serde(default)]
+    pub workspace: WorkspaceSettings,
+    #[serde(default)]
+    pub refresh: RefreshSettings,
 }
 
 fn default_version() -> String {
@@ -160,6 +192,7 @@ impl Default for MarketplaceConfig {
                     local_path: "../Skill-Box".to_string(),
                     last_updated: chrono::Utc::now().to_rfc3339(),
                     auth_type: "public".to_string(),
+                    pinned: false,
                 }
             ]
         }
@@ -179,6 +212,8 @@ pub struct RepositoryInfo {
     pub last_updated: String,
     #[serde(default = "default_auth_type")]
     pub auth_type: String, // "public" | "ssh" | "token"
+    #[serde(default)]
+    pub pinned: bool, // background refresh leaves pinned repositories alone
 }
 
 fn default_auth_type() -> String { "public".to_string() }
@@ -213,13 +248,75 @@ impl Default for AdvancedSettings {
 
 fn default_max_history() -> u32 { 500 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkspaceSettings {
+    #[serde(default)]
+    pub workspace_roots: Vec<String>, // e.g. "~/code"
+    #[serde(default = "default_discovery_depth")]
+    pub max_depth: u32,
+    #[serde(default = "default_discovery_max_dirs")]
+    pub max_directories: u32,
+}
 
-#[derive(Debug, Serialize, Deserialize)]
-#[allow(dead_code)]
+impl Default for WorkspaceSettings {
+    fn default() -> Self {
+        Self {
+            workspace_roots: Vec::new(),
+            max_depth: default_discovery_depth(),
+            max_directories: default_discovery_max_dirs(),
+        }
+    }
+}
+
+// 市场仓库后台刷新
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefreshSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_refresh_interval")]
+    pub interval_minutes: u32,
+    #[serde(default)]
+    pub on_startup: bool,
+    #[serde(default = "true_val")]
+    pub only_when_idle: bool,
+    #[serde(default = "default_idle_minutes")]
+    pub idle_minutes: u32, // no user activity for this long counts as idle
+}
+
+impl Default for RefreshSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: default_refresh_interval(),
+            on_startup: false,
+            only_when_idle: true,
+            idle_minutes: default_idle_minutes(),
+        }
+    }
+}
+
+fn default_refresh_interval() -> u32 { 360 }
+fn default_idle_minutes() -> u32 { 5 }
+
+fn default_discovery_depth() -> u32 { 4 }
+fn default_discovery_max_dirs() -> u32 { 20000 }
+
+
+// 仓库更新结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UpdateResult {
+    pub repo_id: String,
+    pub repo_name: String,
     pub success: bool,
-    pub changes: String,
-    pub commits: Vec<String>,
+    pub error: Option<String>,
+    pub changes: String, // one-line summary
+    pub commits: Vec<String>, // "<short hash> <subject>", newest first
+    pub old_commit: Option<String>,
+    pub new_commit: Option<String>,
+    pub added_plugins: Vec<String>,
+    pub removed_plugins: Vec<String>,
+    pub changed_plugins: Vec<String>, // metadata or files under one of its skills changed
+    pub last_updated: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -241,6 +338,8 @@ pub struct LocalSkill {
     pub has_references: bool,
     pub has_assets: bool,
     pub size_bytes: u64,
+    #[serde(default)]
+    pub link_target: Option<String>,  // 通过符号链接找到时的真实路径
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -276,3 +375,44 @@ pub struct LocalSkillsRegistry {
     pub last_updated: String,  // ISO 8601 时间戳
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegisteredProject {
+    pub path: String,
+    pub name: String,
+    #[serde(default)]
+    pub default_agents: Vec<String>,  // 项目作用域安装未指定代理时使用
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectsRegistry {
+    pub registered_projects: Vec<RegisteredProject>,
+    pub last_updated: String,  // ISO 8601 时间戳
+}
+
+
+// --- Project Discovery Types ---
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KnownProject {
+    pub path: String,
+    pub agents: Vec<String>,  // 检测到项目 skills 目录的代理
+    pub source: String,  // "workspace_scan" | "registered"
+    pub discovered_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KnownProjectsFile {
+    pub projects: Vec<KnownProject>,
+    pub last_updated: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectDiscoveryResult {
+    pub projects_found: Vec<KnownProject>,
+    pub new_projects: usize,
+    pub directories_visited: usize,
+    pub truncated: bool,  // 达到 max_directories 上限
+    pub duration_ms: u64,
+}
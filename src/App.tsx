@@ -1,6 +1,6 @@
 import { useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
-import { getAgents, getMarketplaceData, installPlugin, installLocalSkill, getTranslationConfig, AgentConfig, Plugin, LocalSkill, TranslationConfig } from "./lib/api";
+import { getAgents, detectAgents, getMarketplaceData, installPlugin, installLocalSkill, getTranslationConfig, AgentConfig, Plugin, LocalSkill, TranslationConfig } from "./lib/api";
 import { Topbar } from "./components/Topbar";
 import { SkillMarket } from "./components/SkillMarket";
 import { InstallScope } from "./components/InstallScope";
@@ -10,11 +10,13 @@ import { InstalledPluginsPage } from "./components/InstalledPluginsPage";
 import { Loader2, AlertCircle } from "lucide-react";
 import { Button } from "./components/ui/button";
 import { useAppUpdater } from "./hooks/useAppUpdater";
+import { useActivityReporter } from "./hooks/useActivityReporter";
 import { UpdateBanner } from "./components/UpdateBanner";
 
 function App() {
   const { t } = useTranslation();
   const updater = useAppUpdater();
+  useActivityReporter();
   const [currentPage, setCurrentPage] = useState<"install" | "installed">("install");
   const [step, setStep] = useState(1);
   const [loading, setLoading] = useState(true);
@@ -69,7 +71,17 @@ function App() {
         console.error("Failed to read starred agents for default selection", e);
       }
 
-      // Fallback to strict default if no stars
+      // Fallback to agents detected on this machine
+      if (defaultSelection.length === 0) {
+        try {
+          const detections = await detectAgents();
+          defaultSelection = detections.filter(d => d.status === "installed").map(d => d.agent_id);
+        } catch (e) {
+          console.error("Failed to detect installed agents", e);
+        }
+      }
+
+      // Fallback to strict default if nothing was detected
       if (defaultSelection.length === 0) {
         defaultSelection = ["claude"];
       }
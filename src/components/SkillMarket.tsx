@@ -1,7 +1,7 @@
 import { useState, useMemo, useEffect } from "react";
 import { useTranslation } from "react-i18next";
 import { message, ask } from '@tauri-apps/plugin-dialog';
-import { Plugin, LocalSkill, addMarketplaceRepository, addRepositoryToConfig, removeRepositoryFromConfig, updateRepositoryInConfig, getAppConfig, getPluginSkillsDetails, SkillMetadata, translateBatch } from "../lib/api";
+import { Plugin, LocalSkill, addMarketplaceRepository, removeRepositoryFromConfig, updateRepositoryInConfig, getAppConfig, getPluginSkillsDetails, SkillMetadata, translateBatch } from "../lib/api";
 import { RepositoryInfo } from "../lib/types_config";
 import { Card, CardContent, CardFooter, CardHeader, CardTitle } from "./ui/card";
 import { Badge } from "./ui/badge";
@@ -249,11 +249,9 @@ export function SkillMarket({ plugins, selectedPlugins, onTogglePlugin, onNext,
         setAddError(null);
 
         try {
-            // 1. Validate & Clone
-            const repoInfo = await addMarketplaceRepository(newRepoUrl, extractNameFromUrl(newRepoUrl), "public"); // Automatically extract name from URL
-            // 2. Add to config
-            await addRepositoryToConfig(repoInfo);
-            // 3. Refresh
+            // 1. Clone, validate and save to config (rolled back by the backend on failure)
+            await addMarketplaceRepository(newRepoUrl, extractNameFromUrl(newRepoUrl), "public"); // Automatically extract name from URL
+            // 2. Refresh
             await loadRepositories();
             if (onRefresh) onRefresh();
 
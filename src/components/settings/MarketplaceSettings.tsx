@@ -1,7 +1,7 @@
 import React, { useState } from 'react';
 import { ask } from '@tauri-apps/plugin-dialog';
 import { AppConfig } from '../../lib/types_config';
-import { addMarketplaceRepository, addRepositoryToConfig, removeRepositoryFromConfig, updateRepositoryEnabled, updateRepositoryInConfig } from '../../lib/api';
+import { addMarketplaceRepository, removeRepositoryFromConfig, updateRepositoryEnabled, updateRepositoryInConfig } from '../../lib/api';
 import { Plus, Trash2, GitBranch, AlertCircle, Loader2, Pencil, X, Check } from 'lucide-react';
 import { useTranslation } from 'react-i18next';
 import { extractNameFromUrl } from '../../lib/utils';
@@ -32,13 +32,10 @@ export const MarketplaceSettings: React.FC<MarketplaceSettingsProps> = ({ config
         setError(null);
 
         try {
-            // 1. Call backend to clone and validate
+            // 1. Backend clones, validates and saves to config (rolled back on failure)
             const repoInfo = await addMarketplaceRepository(newRepoUrl, newRepoName || extractNameFromUrl(newRepoUrl), 'public');
 
-            // 2. Add to config via backend
-            await addRepositoryToConfig(repoInfo);
-
-            // 3. Update local state
+            // 2. Update local state
             const newConfig = {
                 ...config,
                 marketplace: {
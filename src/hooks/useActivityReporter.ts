@@ -0,0 +1,31 @@
+import { useEffect } from 'react';
+import { reportUserActivity } from '../lib/api';
+
+// At most one report per window; the backend only needs to know the user isn't idle
+const THROTTLE_MS = 30_000;
+
+const EVENTS = ['pointerdown', 'keydown', 'wheel', 'focus'] as const;
+
+/** Tell the backend about user input so "refresh only when idle" waits for a quiet moment. */
+export function useActivityReporter() {
+    useEffect(() => {
+        let last = 0;
+
+        const report = () => {
+            const now = Date.now();
+            if (now - last < THROTTLE_MS) return;
+            last = now;
+            reportUserActivity().catch(() => {});
+        };
+
+        report();
+        for (const name of EVENTS) {
+            window.addEventListener(name, report, { passive: true });
+        }
+        return () => {
+            for (const name of EVENTS) {
+                window.removeEventListener(name, report);
+            }
+        };
+    }, []);
+}
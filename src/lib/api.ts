@@ -9,6 +9,22 @@ export interface AgentConfig {
     global_path: string;
     icon: string;
     category?: string; // "Core" | "Community"
+    rule_format?: RuleFormat;
+    capabilities: AgentCapabilities;
+}
+
+export interface AgentCapabilities {
+    supports_scripts: boolean;
+    supports_nested_skills: boolean;
+    max_skill_size_bytes?: number;
+    max_description_length?: number;
+    frontmatter_fields?: string[];
+}
+
+export interface RuleFormat {
+    kind: "cursor_mdc" | "windsurf_rule" | "copilot_instructions";
+    project_path: string;
+    global_path?: string;
 }
 
 export interface Author {
@@ -47,24 +63,148 @@ export async function getAgents(): Promise<AgentConfig[]> {
     return invoke("get_agents");
 }
 
+export interface PathVariable {
+    name: string;
+    value?: string;
+    source: "env" | "default" | "fallback" | "unset";
+}
+
+export interface AgentPathDiagnostics {
+    agent_id: string;
+    global_template: string;
+    global_resolved: string;
+    global_exists: boolean;
+    custom_override: boolean;
+    project_template: string;
+    project_resolved?: string;
+    variables: PathVariable[];
+    error?: string; // agent skipped: its path uses an unset variable
+}
+
+export async function getAgentPathDiagnostics(projectPath?: string): Promise<AgentPathDiagnostics[]> {
+    return invoke("get_agent_path_diagnostics", { projectPath });
+}
+
+export interface AgentDetection {
+    agent_id: string;
+    status: "installed" | "likely_installed" | "absent";
+    global_config_dir?: string;
+    global_skills_exists: boolean;
+    project_marker?: string;
+    project_marker_exists: boolean;
+    project_skills_exists: boolean;
+    evidence: string[];
+}
+
+export async function detectAgents(projectPath?: string): Promise<AgentDetection[]> {
+    return invoke("detect_agents", { projectPath });
+}
+
 export async function getMarketplaceData(): Promise<MarketplaceData> {
     return invoke("get_marketplace_data");
 }
 
+export interface MarketplaceQuery {
+    category?: string;
+    author?: string;
+    source_repo?: string;
+    has_scripts?: boolean;
+    installed?: boolean;
+    installed_in_agent?: string;
+    sort_by?: "name" | "category" | "skills" | "installed";
+    sort_desc?: boolean;
+    page?: number; // 1-based
+    page_size?: number;
+}
+
+export interface MarketplaceEntry extends Plugin {
+    has_scripts: boolean;
+    installed: boolean;
+    installed_agents: string[];
+}
+
+export interface FacetCount {
+    value: string;
+    count: number;
+}
+
+export interface MarketplaceQueryResult {
+    plugins: MarketplaceEntry[];
+    total: number;
+    page: number;
+    page_size: number;
+    total_pages: number;
+    facets: {
+        categories: FacetCount[];
+        repos: FacetCount[];
+        authors: FacetCount[];
+    };
+}
+
+export async function queryMarketplace(query: MarketplaceQuery): Promise<MarketplaceQueryResult> {
+    return invoke("query_marketplace", { query });
+}
+
 export async function installPlugin(
     pluginName: string,
     agents: string[],
     scopeType: "global" | "project",
-    scopePath?: string
+    scopePath?: string,
+    nativeFormat?: boolean,
+    linkPolicy?: "follow" | "preserve" | "skip"
 ): Promise<string> {
     return invoke("install_plugin", {
         pluginName,
         agents,
         scopeType,
-        scopePath
+        scopePath,
+        nativeFormat,
+        linkPolicy
     });
 }
 
+export interface CompatibilityIssue {
+    agent_id: string;
+    skill_name: string;
+    code: string;
+    severity: "warning" | "error";
+    message: string;
+}
+
+export interface InstallPlanItem {
+    agent_id: string;
+    skill_name: string;
+    source_path: string;
+    target_path?: string;
+    action: "copy" | "convert" | "skip";
+}
+
+export interface InstallPlan {
+    items: InstallPlanItem[];
+    warnings: CompatibilityIssue[];
+    blocked: boolean;
+}
+
+export async function planPluginInstall(
+    pluginName: string,
+    agents: string[],
+    scopeType: "global" | "project",
+    scopePath?: string,
+    nativeFormat?: boolean
+): Promise<InstallPlan> {
+    return invoke("plan_plugin_install", { pluginName, agents, scopeType, scopePath, nativeFormat });
+}
+
+export async function planLocalSkillInstall(
+    skillPath: string,
+    scope: "global" | "project",
+    selectedAgents: string[],
+    scopePath?: string,
+    nativeFormat?: boolean
+): Promise<InstallPlan> {
+    return invoke("plan_local_skill_install", { skillPath, scope, selectedAgents, scopePath, nativeFormat });
+}
+
 export async function getPluginSkillsDetails(
     pluginName: string,
     skillPaths: string[]
@@ -95,6 +235,7 @@ export interface InstalledPlugin {
     size_bytes: number;
     paths_by_agent: Record<string, string>;
     source_type?: string;
+    linked_paths: Record<string, string>; // installed path -> real target
 }
 
 export interface ScanSummary {
@@ -107,6 +248,32 @@ export interface ScanResult {
     by_scope: ScanSummary;
     by_agent: Record<string, number>;
     plugins: InstalledPlugin[];
+    skills: InstalledSkill[];
+    duration_ms: number;
+    index_stats: IndexStats;
+}
+
+export interface InstalledSkill {
+    name: string;
+    plugin_name: string;
+    grouped_by: "provenance" | "marketplace" | "standalone";
+    description?: string;
+    category?: string;
+    version?: string;
+    agent_id: string;
+    scope: "global" | "project";
+    project_path?: string;
+    path: string;
+    size_bytes: number;
+    installed_at: string;
+    content_hash: string;
+    source_type?: string;
+    link_target?: string;
+}
+
+export interface IndexStats {
+    cache_hits: number;
+    cache_misses: number;
 }
 
 
@@ -126,9 +293,249 @@ export interface HistoryRecord {
 
 export async function scanInstalledPlugins(
     scope?: "global" | "project",
-    projectPath?: string
+    projectPath?: string,
+    refresh?: boolean
 ): Promise<ScanResult> {
-    return invoke("scan_installed_plugins", { scope, projectPath });
+    return invoke("scan_installed_plugins", { scope, projectPath, refresh });
+}
+
+export async function clearScanIndex(): Promise<number> {
+    return invoke("clear_scan_index");
+}
+
+// 技能目录实时监听（后端通过 "skill-changed" 事件推送 SkillChangeEvent）
+export const SKILL_CHANGED_EVENT = "skill-changed";
+
+export interface WatchedRoot {
+    path: string;
+    kind: "agent_global" | "agent_project" | "local_directory";
+    agent_id?: string;
+    project_path?: string;
+}
+
+export interface SkillChangeEvent {
+    change: "added" | "changed" | "removed";
+    skill_name: string;
+    path: string;
+    root: WatchedRoot;
+}
+
+export async function startSkillWatcher(): Promise<WatchedRoot[]> {
+    return invoke("start_skill_watcher");
+}
+
+export async function stopSkillWatcher(): Promise<boolean> {
+    return invoke("stop_skill_watcher");
+}
+
+export interface SkillFrontmatter {
+    name?: string;
+    description?: string;
+    license?: string;
+    allowed_tools: string[];
+    version?: string;
+    metadata: Record<string, unknown>;
+    extra: Record<string, unknown>;
+}
+
+export interface ParsedSkillMd {
+    frontmatter?: SkillFrontmatter;
+    keys: string[];
+    key_lines: Record<string, number>;
+    body: string;
+    body_line: number;
+}
+
+/** Parse the SKILL.md of a skill folder (or a SKILL.md path). Errors mention the line. */
+export async function readSkillFrontmatter(skillPath: string): Promise<ParsedSkillMd> {
+    return invoke("read_skill_frontmatter", { skillPath });
+}
+
+export interface HealthFinding {
+    code: string;
+    severity: "error" | "warning";
+    message: string;
+    file: string;
+    line?: number;
+}
+
+export interface SkillHealth {
+    skill_name: string;
+    path: string;
+    agents: string[];
+    scope: "global" | "project";
+    project_path?: string;
+    likely_ignored: boolean;
+    findings: HealthFinding[];
+}
+
+export interface HealthReport {
+    skills_checked: number;
+    error_count: number;
+    warning_count: number;
+    ignored_count: number;
+    skills: SkillHealth[];
+    duration_ms: number;
+}
+
+export async function checkSkillsHealth(
+    scope?: "global" | "project",
+    projectPath?: string
+): Promise<HealthReport> {
+    return invoke("check_skills_health", { scope, projectPath });
+}
+
+export interface FileDiff {
+    path: string;
+    status: "added" | "removed" | "modified" | "unchanged";
+    binary: boolean;
+    left_size?: number;
+    right_size?: number;
+    additions: number;
+    deletions: number;
+    unified?: string; // changed text files under 1 MB
+}
+
+export interface SkillDiff {
+    left_path: string;
+    right_path: string;
+    identical: boolean;
+    summary: {
+        added: number;
+        removed: number;
+        modified: number;
+        unchanged: number;
+        binary_changed: number;
+        additions: number;
+        deletions: number;
+    };
+    files: FileDiff[];
+}
+
+export async function diffSkillDirs(
+    leftPath: string,
+    rightPath: string,
+    contextLines?: number,
+    includeUnchanged?: boolean
+): Promise<SkillDiff> {
+    return invoke("diff_skill_dirs", { leftPath, rightPath, contextLines, includeUnchanged });
+}
+
+/** Diff an installed skill against the marketplace folder it came from (source on the left) */
+export async function diffSkillWithSource(installedPath: string, contextLines?: number): Promise<SkillDiff> {
+    return invoke("diff_skill_with_source", { installedPath, contextLines });
+}
+
+export interface LintRule {
+    id: string;
+    severity: "error" | "warning";
+    fixable: boolean;
+    description: string;
+}
+
+export interface LintDiagnostic {
+    rule: string;
+    severity: "error" | "warning";
+    message: string;
+    file: string;
+    line?: number;
+    fixable: boolean;
+}
+
+export interface LintReport {
+    skill_path: string;
+    diagnostics: LintDiagnostic[];
+    error_count: number;
+    warning_count: number;
+    fixable_count: number;
+}
+
+export interface LintFixResult {
+    applied: LintDiagnostic[];
+    failed: string[];
+    report: LintReport;
+}
+
+export async function listLintRules(): Promise<LintRule[]> {
+    return invoke("list_lint_rules");
+}
+
+export async function lintSkill(skillPath: string): Promise<LintReport> {
+    return invoke("lint_skill", { skillPath });
+}
+
+/** Apply autofixes; all fixable rules when `rules` is omitted */
+export async function fixSkillLint(skillPath: string, rules?: string[]): Promise<LintFixResult> {
+    return invoke("fix_skill_lint", { skillPath, rules });
+}
+
+export interface SkillCopy {
+    path: string;
+    agents: string[];
+    scope: "global" | "project";
+    project_path?: string;
+    content_hash: string;
+    size_bytes: number;
+    variant: number;
+}
+
+export interface SkillResolution {
+    agent_id: string;
+    project_path?: string;
+    effective_path: string;
+    shadowed_paths: string[];
+    ambiguous: boolean;
+}
+
+export interface SkillConflictGroup {
+    skill_name: string;
+    copies: SkillCopy[];
+    variants: number;
+    diverged: boolean;
+    marketplace_plugins: string[];
+    resolutions: SkillResolution[];
+}
+
+export interface ConflictReport {
+    skills_checked: number;
+    groups: SkillConflictGroup[];
+    diverged_count: number;
+    shadowed_count: number;
+    duration_ms: number;
+}
+
+export async function analyzeSkillConflicts(
+    scope?: "global" | "project",
+    projectPath?: string
+): Promise<ConflictReport> {
+    return invoke("analyze_skill_conflicts", { scope, projectPath });
+}
+
+export interface KnownProject {
+    path: string;
+    agents: string[];
+    source: string; // "workspace_scan" | "registered"
+    discovered_at: string;
+}
+
+export interface ProjectDiscoveryResult {
+    projects_found: KnownProject[];
+    new_projects: number;
+    directories_visited: number;
+    truncated: boolean;
+    duration_ms: number;
+}
+
+export async function discoverProjects(roots?: string[]): Promise<ProjectDiscoveryResult> {
+    return invoke("discover_projects", { roots });
+}
+
+export async function listKnownProjects(): Promise<KnownProject[]> {
+    return invoke("list_known_projects");
+}
+
+export async function removeKnownProject(path: string): Promise<boolean> {
+    return invoke("remove_known_project", { path });
 }
 
 export async function searchInstalledPlugins(
@@ -143,6 +550,31 @@ export async function searchInstalledPlugins(
     });
 }
 
+export interface FieldMatch {
+    field: "name" | "category" | "description" | "author" | "body";
+    text: string;
+    ranges: [number, number][]; // [start, end) in characters of `text`
+}
+
+export interface SearchHit {
+    origin: "installed" | "marketplace" | "local";
+    name: string;
+    description?: string;
+    plugin_name?: string;
+    path?: string;
+    agents: string[];
+    score: number;
+    matches: FieldMatch[];
+}
+
+export async function searchSkills(
+    query: string,
+    origins?: ("installed" | "marketplace" | "local")[],
+    limit?: number
+): Promise<SearchHit[]> {
+    return invoke("search_skills", { query, origins, limit });
+}
+
 
 
 
@@ -178,21 +610,72 @@ export async function openProjectFolder(path: string): Promise<void> {
 }
 
 // Settings & Git Config
-import { AppConfig, GeneralSettings, RepositoryInfo } from "./types_config";
+import {
+    AppConfig,
+    CredentialInfo,
+    GeneralSettings,
+    RefreshSettings,
+    RefreshStatus,
+    RefreshSummary,
+    RepoCredential,
+    RepositoryInfo,
+    RepositoryStatus,
+    UpdateResult,
+    WorkspaceSettings
+} from "./types_config";
+
+/** Credentials for "token" or "ssh" repositories; stored encrypted, never in config.json */
+export interface RepositoryAuth {
+    username?: string;
+    token?: string;
+    sshKeyPath?: string; // omitted: use the SSH agent
+}
 
 export async function addMarketplaceRepository(
     url: string,
     name: string,
     authType: string,
-    authToken?: string
+    auth: RepositoryAuth = {}
 ): Promise<RepositoryInfo> {
-    return invoke("add_marketplace_repository", { url, name, authType, authToken });
+    return invoke("add_marketplace_repository", {
+        url,
+        name,
+        authType,
+        username: auth.username,
+        authToken: auth.token,
+        sshKeyPath: auth.sshKeyPath,
+    });
 }
 
 export async function validateMarketplaceRepository(path: string): Promise<any> {
     return invoke("validate_marketplace_repository", { path });
 }
 
+export async function updateMarketplaceRepository(repoId: string): Promise<UpdateResult> {
+    return invoke("update_marketplace_repository", { repoId });
+}
+
+/** Fast-forward every enabled git repository; failures are reported per repository */
+export async function updateAllMarketplaceRepositories(): Promise<UpdateResult[]> {
+    return invoke("update_all_marketplace_repositories");
+}
+
+export async function getRepositoryStatus(repoId: string): Promise<RepositoryStatus> {
+    return invoke("get_repository_status", { repoId });
+}
+
+export async function setRepositoryCredential(repoId: string, credential: RepoCredential): Promise<CredentialInfo> {
+    return invoke("set_repository_credential", { repoId, credential });
+}
+
+export async function removeRepositoryCredential(repoId: string): Promise<void> {
+    return invoke("remove_repository_credential", { repoId });
+}
+
+export async function getRepositoryCredentialInfo(repoId: string): Promise<CredentialInfo | null> {
+    return invoke("get_repository_credential_info", { repoId });
+}
+
 export async function getAppConfig(): Promise<AppConfig> {
     return invoke("get_app_config");
 }
@@ -205,6 +688,27 @@ export async function updateGeneralSettings(settings: GeneralSettings): Promise<
     return invoke("update_general_settings", { settings });
 }
 
+export async function updateWorkspaceSettings(settings: WorkspaceSettings): Promise<void> {
+    return invoke("update_workspace_settings", { settings });
+}
+
+export async function updateRefreshSettings(settings: RefreshSettings): Promise<void> {
+    return invoke("update_refresh_settings", { settings });
+}
+
+export async function refreshMarketplacesNow(): Promise<RefreshSummary> {
+    return invoke("refresh_marketplaces_now");
+}
+
+export async function getRefreshStatus(): Promise<RefreshStatus> {
+    return invoke("get_refresh_status");
+}
+
+/** Tell the scheduler the user is active, so "only when idle" refreshes wait */
+export async function reportUserActivity(): Promise<void> {
+    return invoke("report_user_activity");
+}
+
 export async function addRepositoryToConfig(repo: RepositoryInfo): Promise<void> {
     return invoke("add_repository_to_config", { repo });
 }
@@ -217,6 +721,10 @@ export async function updateRepositoryEnabled(repoId: string, enabled: boolean):
     return invoke("update_repository_enabled", { repoId, enabled });
 }
 
+export async function updateRepositoryPinned(repoId: string, pinned: boolean): Promise<void> {
+    return invoke("update_repository_pinned", { repoId, pinned });
+}
+
 export async function updateRepositoryInConfig(repoId: string, name: string, url: string): Promise<void> {
     return invoke("update_repository_in_config", { repoId, name, url });
 }
@@ -232,6 +740,7 @@ export interface LocalSkill {
     has_references: boolean;
     has_assets: boolean;
     size_bytes: number;
+    link_target?: string;
 }
 
 export interface LocalSkillScanResult {
@@ -271,16 +780,130 @@ export async function installLocalSkill(
     skillPath: string,
     scope: "global" | "project",
     selectedAgents: string[],
-    scopePath?: string
+    scopePath?: string,
+    nativeFormat?: boolean,
+    linkPolicy?: "follow" | "preserve" | "skip"
 ): Promise<string> {
     return invoke("install_local_skill", {
         skillPath,
         scope,
         selectedAgents,
-        scopePath
+        scopePath,
+        nativeFormat,
+        linkPolicy
     });
 }
 
+// --- Skill Templates API ---
+
+export interface SkillTemplate {
+    id: string;
+    name: string;
+    description: string;
+    builtin: boolean;
+    path?: string; // custom templates only
+}
+
+export interface CreateSkillRequest {
+    directory: string; // a registered local skills directory
+    template: string;
+    name: string;
+    description: string;
+    license?: string;
+    allowed_tools?: string[];
+}
+
+export interface CreatedSkill {
+    path: string;
+    files: string[];
+}
+
+export async function listSkillTemplates(): Promise<SkillTemplate[]> {
+    return invoke("list_skill_templates");
+}
+
+export async function createSkillFromTemplate(request: CreateSkillRequest): Promise<CreatedSkill> {
+    return invoke("create_skill_from_template", { request });
+}
+
+// --- Skill Editor API ---
+
+export interface SkillFileNode {
+    name: string;
+    path: string; // relative to the skill root
+    is_dir: boolean;
+    is_symlink: boolean;
+    size_bytes: number;
+    children: SkillFileNode[];
+}
+
+export interface SkillFileContent {
+    path: string;
+    content: string;
+    size_bytes: number;
+    modified_at?: string;
+    can_undo: boolean;
+}
+
+export interface SkillFileWriteResult {
+    path: string;
+    size_bytes: number;
+    can_undo: boolean;
+    lint?: LintReport; // after saving SKILL.md
+}
+
+export async function listSkillFiles(skillPath: string): Promise<SkillFileNode[]> {
+    return invoke("list_skill_files", { skillPath });
+}
+
+export async function readSkillFile(skillPath: string, filePath: string): Promise<SkillFileContent> {
+    return invoke("read_skill_file", { skillPath, filePath });
+}
+
+export async function writeSkillFile(skillPath: string, filePath: string, content: string): Promise<SkillFileWriteResult> {
+    return invoke("write_skill_file", { skillPath, filePath, content });
+}
+
+/** Undo the last write to a file. Resolves to the restored content, or null when the edit created the file. */
+export async function undoSkillFileEdit(skillPath: string, filePath: string): Promise<string | null> {
+    return invoke("undo_skill_file_edit", { skillPath, filePath });
+}
+
+// --- Projects API ---
+
+export interface RegisteredProject {
+    path: string;
+    name: string;
+    default_agents: string[];
+    tags: string[];
+}
+
+export async function registerProject(
+    path: string,
+    name?: string,
+    defaultAgents?: string[],
+    tags?: string[]
+): Promise<boolean> {
+    return invoke<boolean>('register_project', { path, name, defaultAgents, tags });
+}
+
+export async function unregisterProject(path: string): Promise<boolean> {
+    return invoke<boolean>('unregister_project', { path });
+}
+
+export async function listRegisteredProjects(tag?: string): Promise<RegisteredProject[]> {
+    return invoke<RegisteredProject[]>('list_registered_projects', { tag });
+}
+
+export async function updateProject(
+    path: string,
+    name?: string,
+    defaultAgents?: string[],
+    tags?: string[]
+): Promise<boolean> {
+    return invoke<boolean>('update_project', { path, name, defaultAgents, tags });
+}
+
 // --- Translation API ---
 
 export interface TranslationConfig {
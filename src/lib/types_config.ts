@@ -4,6 +4,8 @@ export interface AppConfig {
     marketplace: MarketplaceConfig;
     agents: AgentsConfig;
     advanced: AdvancedSettings;
+    workspace: WorkspaceSettings;
+    refresh: RefreshSettings;
 }
 
 export interface GeneralSettings {
@@ -15,6 +17,20 @@ export interface GeneralSettings {
     confirm_before_install: boolean;
 }
 
+export interface WorkspaceSettings {
+    workspace_roots: string[]; // e.g. "~/code"
+    max_depth: number;
+    max_directories: number;
+}
+
+export interface RefreshSettings {
+    enabled: boolean;
+    interval_minutes: number;
+    on_startup: boolean; // refresh once after launch, even when the schedule is off
+    only_when_idle: boolean;
+    idle_minutes: number;
+}
+
 export interface MarketplaceConfig {
     repositories: RepositoryInfo[];
 }
@@ -29,6 +45,93 @@ export interface RepositoryInfo {
     local_path: string;
     last_updated: string;
     auth_type: string; // "public" | "ssh" | "token"
+    pinned: boolean; // skipped by background refresh
+}
+
+export interface UpdateResult {
+    repo_id: string;
+    repo_name: string;
+    success: boolean;
+    error?: string;
+    changes: string;
+    commits: string[]; // "<short hash> <subject>", newest first
+    old_commit?: string;
+    new_commit?: string;
+    added_plugins: string[];
+    removed_plugins: string[];
+    changed_plugins: string[];
+    last_updated: string;
+}
+
+export interface SkippedRepo {
+    repo_id: string;
+    repo_name: string;
+    reason: "pinned" | "not_git" | "backoff";
+    retry_after?: string;
+}
+
+/** Payload of the "marketplace-refresh" event */
+export interface RefreshSummary {
+    trigger: "startup" | "interval" | "manual";
+    started_at: string;
+    finished_at: string;
+    results: UpdateResult[];
+    skipped: SkippedRepo[];
+    updated_count: number;
+    failed_count: number;
+}
+
+export interface RepoCredential {
+    kind: "token" | "ssh";
+    host?: string; // filled in from the repository URL
+    username?: string;
+    token?: string;
+    ssh_key_path?: string; // omitted: use the SSH agent
+    ssh_passphrase?: string;
+}
+
+// Credential summary without secrets
+export interface CredentialInfo {
+    repo_id: string;
+    kind: "token" | "ssh";
+    host?: string;
+    username?: string;
+    has_token: boolean;
+    ssh_key_path?: string;
+    has_passphrase: boolean;
+}
+
+// Payload of the "git-progress" event during clone and fetch
+export interface GitProgress {
+    repo_id: string;
+    operation: "clone" | "fetch";
+    received_objects: number;
+    total_objects: number;
+    indexed_deltas: number;
+    total_deltas: number;
+    received_bytes: number;
+}
+
+export interface RepositoryStatus {
+    is_git: boolean;
+    branch?: string;
+    head?: string;
+    upstream?: string;
+    ahead: number;
+    behind: number;
+    dirty: boolean;
+}
+
+export interface RefreshStatus {
+    running: boolean;
+    last_run?: string;
+    next_run?: string;
+    backoff: {
+        repo_id: string;
+        failures: number;
+        retry_after: string;
+        last_error: string;
+    }[];
 }
 
 export interface AgentsConfig {